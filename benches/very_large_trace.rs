@@ -0,0 +1,174 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use pal_rs::{
+  mmu::trace::Trace,
+  pal::{PALAlgorithm, PAL},
+  simulator::Simulator,
+};
+
+const TRACE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/traces/very_large_trace.txt");
+const PAGE_SIZE: usize = 4096;
+
+fn pal_insert(c: &mut Criterion) {
+  let trace = Trace::from_file(TRACE).unwrap();
+  let next_uses = trace.next_uses(PAGE_SIZE);
+  let pages = trace
+    .into_iter()
+    .map(|address| address.page(PAGE_SIZE))
+    .collect::<Vec<_>>();
+
+  let mut group = c.benchmark_group("pal_insert");
+  for algorithm in [
+    PALAlgorithm::LRU,
+    PALAlgorithm::Counter,
+    PALAlgorithm::SecondChance,
+    PALAlgorithm::Fifo,
+    PALAlgorithm::Optimal,
+    PALAlgorithm::EnhancedSecondChance,
+    PALAlgorithm::Clock,
+    PALAlgorithm::Aging,
+    PALAlgorithm::LfuDecay,
+    PALAlgorithm::Mfu,
+    PALAlgorithm::WorkingSet,
+    PALAlgorithm::WSClock,
+    PALAlgorithm::ARC,
+    PALAlgorithm::LIRS,
+    PALAlgorithm::TwoQueue,
+    PALAlgorithm::ClockPro,
+    PALAlgorithm::Sieve,
+    PALAlgorithm::S3Fifo,
+    PALAlgorithm::Random,
+  ] {
+    for frames in [16, 64, 256, 65536] {
+      group.bench_with_input(
+        BenchmarkId::new(format!("{algorithm:?}"), frames),
+        &frames,
+        |b, &frames| {
+          b.iter(|| {
+            let mut pal = PAL::new(algorithm, frames);
+            pal.set_lookahead(next_uses.clone());
+            pages.iter().for_each(|&page| {
+              pal.insert(page);
+            });
+          })
+        },
+      );
+    }
+  }
+  group.finish();
+}
+
+fn translate(c: &mut Criterion) {
+  let mut group = c.benchmark_group("translate");
+  for frames in [16, 64, 256] {
+    group.bench_with_input(BenchmarkId::new("LRU", frames), &frames, |b, &frames| {
+      b.iter(|| {
+        let mut simulator = Simulator::new(PALAlgorithm::LRU, frames, PAGE_SIZE);
+        simulator.run(&Trace::from_file(TRACE).unwrap());
+      })
+    });
+  }
+  group.finish();
+}
+
+/// Translation as it was before the PAL tables and the page table were indexed: LRU scans its
+/// entries on every reference, and every eviction scans the whole flat page table for the entry of
+/// the victim frame. Kept to reproduce the speedup of the indexed path.
+mod baseline {
+  #[derive(Debug, Clone, Copy, Default)]
+  struct Entry {
+    frame: usize,
+    valid: bool,
+  }
+
+  pub struct Translator {
+    /// Loaded frames with their last access, in load order
+    lru: Vec<(usize, u64)>,
+    frames: usize,
+    page_table: Vec<Entry>,
+    now: u64,
+  }
+
+  impl Translator {
+    pub fn new(frames: usize, pages: usize) -> Self {
+      Self {
+        lru: Vec::with_capacity(frames),
+        frames,
+        page_table: vec![Entry::default(); pages],
+        now: 0,
+      }
+    }
+
+    /// Whether `page` hit
+    pub fn translate(&mut self, page: usize) -> bool {
+      self.now += 1;
+      let entry = self.page_table[page];
+      if entry.valid {
+        let index = self.lru.iter().position(|&(frame, _)| frame == entry.frame);
+        self.lru[index.unwrap()].1 = self.now;
+        return true;
+      }
+
+      let frame = match self.lru.len() < self.frames {
+        true => self.lru.len(),
+        false => {
+          let index = (0..self.lru.len()).min_by_key(|&i| self.lru[i].1).unwrap();
+          let (frame, _) = self.lru.remove(index);
+          for entry in self.page_table.iter_mut() {
+            if entry.valid && entry.frame == frame {
+              entry.valid = false;
+            }
+          }
+          frame
+        }
+      };
+      self.page_table[page] = Entry { frame, valid: true };
+      self.lru.push((frame, self.now));
+      false
+    }
+  }
+}
+
+/// References of the trace the baseline runs, a full run of it taking tens of seconds
+const BASELINE_REFERENCES: usize = 500;
+
+/// Both paths over the same references. With 64 frames the baseline took ~630 ms and the indexed
+/// path ~2.4 ms, most of it allocating the flat page table
+fn lru_baseline(c: &mut Criterion) {
+  let trace = Trace::from_file(TRACE).unwrap();
+  let pages = trace
+    .iter()
+    .take(BASELINE_REFERENCES)
+    .map(|address| address.page(PAGE_SIZE))
+    .collect::<Vec<_>>();
+  let mut prefix = Trace::new();
+  trace
+    .into_iter()
+    .take(BASELINE_REFERENCES)
+    .for_each(|address| prefix.add(address));
+
+  let mut group = c.benchmark_group("lru_baseline");
+  for frames in [16, 64, 256] {
+    group.bench_with_input(BenchmarkId::new("linear_scan", frames), &frames, |b, &frames| {
+      b.iter(|| {
+        let mut translator = baseline::Translator::new(frames, 1 << (32 - PAGE_SIZE.ilog2()));
+        pages.iter().for_each(|&page| {
+          translator.translate(page);
+        });
+      })
+    });
+    group.bench_with_input(BenchmarkId::new("indexed", frames), &frames, |b, &frames| {
+      b.iter(|| {
+        let mut simulator = Simulator::new(PALAlgorithm::LRU, frames, PAGE_SIZE);
+        simulator.run(&prefix);
+      })
+    });
+  }
+  group.finish();
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().sample_size(10);
+  targets = pal_insert, translate, lru_baseline
+}
+criterion_main!(benches);
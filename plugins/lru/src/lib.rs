@@ -0,0 +1,132 @@
+//! LRU as a `pal_rs` plugin. It does not depend on `pal_rs`: the types below mirror its
+//! `pal::plugin` C ABI, which is all a policy kept out of the repository needs.
+
+use std::{
+  collections::{BTreeMap, HashMap},
+  ffi::{c_char, c_void},
+};
+
+const PLUGIN_ABI_VERSION: u32 = 1;
+
+#[repr(C)]
+pub struct PluginAccess {
+  pub page: usize,
+  pub write: bool,
+  pub time: u64,
+}
+
+#[repr(C)]
+pub struct PluginVTable {
+  pub abi_version: u32,
+  pub name: unsafe extern "C" fn() -> *const c_char,
+  pub new: unsafe extern "C" fn(capacity: usize) -> *mut c_void,
+  pub clone: unsafe extern "C" fn(state: *const c_void) -> *mut c_void,
+  pub drop: unsafe extern "C" fn(state: *mut c_void),
+  pub on_hit: unsafe extern "C" fn(state: *mut c_void, access: *const PluginAccess, frame: usize),
+  pub on_fault: Option<unsafe extern "C" fn(state: *mut c_void, access: *const PluginAccess)>,
+  pub choose_victim: unsafe extern "C" fn(state: *mut c_void) -> usize,
+  pub on_evict: unsafe extern "C" fn(state: *mut c_void, frame: usize),
+  pub on_load: unsafe extern "C" fn(state: *mut c_void, access: *const PluginAccess, frame: usize),
+  pub resident: unsafe extern "C" fn(state: *const c_void) -> usize,
+  pub release: Option<unsafe extern "C" fn(state: *mut c_void, frame: *mut usize) -> bool>,
+  pub write_back: Option<unsafe extern "C" fn(state: *mut c_void, frame: *mut usize) -> bool>,
+  pub set_lookahead:
+    Option<unsafe extern "C" fn(state: *mut c_void, next_uses: *const usize, len: usize)>,
+  pub parameters: Option<
+    unsafe extern "C" fn(
+      state: *const c_void,
+      names: *mut *const c_char,
+      values: *mut usize,
+      capacity: usize,
+    ) -> usize,
+  >,
+  pub error: Option<unsafe extern "C" fn(state: *const c_void) -> *const c_char>,
+}
+
+/// Loaded frames by their last use, and the other way around
+#[derive(Debug, Clone, Default)]
+struct Lru {
+  last_uses: HashMap<usize, u64>,
+  frames: BTreeMap<u64, usize>,
+}
+
+impl Lru {
+  fn touch(&mut self, frame: usize, time: u64) {
+    if let Some(last_use) = self.last_uses.insert(frame, time) {
+      self.frames.remove(&last_use);
+    }
+    self.frames.insert(time, frame);
+  }
+}
+
+unsafe fn lru<'a>(state: *mut c_void) -> &'a mut Lru {
+  &mut *(state as *mut Lru)
+}
+
+unsafe extern "C" fn name() -> *const c_char {
+  c"lru".as_ptr()
+}
+
+unsafe extern "C" fn new(capacity: usize) -> *mut c_void {
+  let lru = Lru {
+    last_uses: HashMap::with_capacity(capacity),
+    frames: BTreeMap::new(),
+  };
+  Box::into_raw(Box::new(lru)) as *mut c_void
+}
+
+unsafe extern "C" fn clone(state: *const c_void) -> *mut c_void {
+  let lru = (*(state as *const Lru)).clone();
+  Box::into_raw(Box::new(lru)) as *mut c_void
+}
+
+unsafe extern "C" fn drop(state: *mut c_void) {
+  std::mem::drop(Box::from_raw(state as *mut Lru));
+}
+
+unsafe extern "C" fn on_hit(state: *mut c_void, access: *const PluginAccess, frame: usize) {
+  lru(state).touch(frame, (*access).time);
+}
+
+unsafe extern "C" fn choose_victim(state: *mut c_void) -> usize {
+  *lru(state).frames.values().next().unwrap()
+}
+
+unsafe extern "C" fn on_evict(state: *mut c_void, frame: usize) {
+  let lru = lru(state);
+  if let Some(last_use) = lru.last_uses.remove(&frame) {
+    lru.frames.remove(&last_use);
+  }
+}
+
+unsafe extern "C" fn on_load(state: *mut c_void, access: *const PluginAccess, frame: usize) {
+  lru(state).touch(frame, (*access).time);
+}
+
+unsafe extern "C" fn resident(state: *const c_void) -> usize {
+  (*(state as *const Lru)).last_uses.len()
+}
+
+static VTABLE: PluginVTable = PluginVTable {
+  abi_version: PLUGIN_ABI_VERSION,
+  name,
+  new,
+  clone,
+  drop,
+  on_hit,
+  on_fault: None,
+  choose_victim,
+  on_evict,
+  on_load,
+  resident,
+  release: None,
+  write_back: None,
+  set_lookahead: None,
+  parameters: None,
+  error: None,
+};
+
+#[no_mangle]
+pub extern "C" fn pal_plugin_vtable() -> *const PluginVTable {
+  &VTABLE
+}
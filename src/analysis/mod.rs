@@ -0,0 +1,2 @@
+pub mod stack_distance;
+pub mod working_set;
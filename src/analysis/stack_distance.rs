@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use crate::{
+  cli::mrc::MrcOptions,
+  mmu::trace::Trace,
+  pal::PALAlgorithm,
+  report::{self, Report},
+};
+
+/// Fenwick tree over trace positions, counting how many positions are set in a prefix
+struct Fenwick {
+  tree: Vec<i64>,
+}
+
+impl Fenwick {
+  fn new(size: usize) -> Self {
+    Self {
+      tree: vec![0; size + 1],
+    }
+  }
+
+  fn add(&mut self, position: usize, delta: i64) {
+    let mut i = position + 1;
+    while i < self.tree.len() {
+      self.tree[i] += delta;
+      i += i & i.wrapping_neg();
+    }
+  }
+
+  /// Sum of the positions strictly before `position`
+  fn prefix(&self, position: usize) -> i64 {
+    let mut i = position;
+    let mut sum = 0;
+    while i > 0 {
+      sum += self.tree[i];
+      i -= i & i.wrapping_neg();
+    }
+    sum
+  }
+}
+
+/// LRU stack distances of a trace, computed in a single pass (Mattson et al.).
+///
+/// The stack distance of a reference is the depth of its page in the LRU stack, i.e. the number
+/// of distinct pages referenced since the previous reference to it, itself included. LRU with `n`
+/// frames faults exactly on the references whose distance is greater than `n`, plus the cold
+/// misses, so one histogram yields the fault count of every frame count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackDistances {
+  /// `histogram[d]` is the number of references with stack distance `d`
+  pub histogram: Vec<usize>,
+  /// First references to a page, which fault whatever the number of frames
+  pub cold_misses: usize,
+  pub references: usize,
+}
+
+impl StackDistances {
+  pub fn from_trace(trace: &Trace, page_size: usize) -> Self {
+    Self::from_pages(trace.iter().map(|address| address.page(page_size)))
+  }
+
+  pub fn from_pages(pages: impl ExactSizeIterator<Item = usize>) -> Self {
+    let references = pages.len();
+    // marks the position of the latest reference to every page seen so far
+    let mut latest = Fenwick::new(references);
+    let mut last_seen = HashMap::new();
+    let mut histogram = vec![0];
+    let mut cold_misses = 0;
+
+    for (position, page) in pages.enumerate() {
+      match last_seen.insert(page, position) {
+        Some(previous) => {
+          let distance = (latest.prefix(position) - latest.prefix(previous + 1)) as usize + 1;
+          if histogram.len() <= distance {
+            histogram.resize(distance + 1, 0);
+          }
+          histogram[distance] += 1;
+          latest.add(previous, -1);
+        }
+        None => cold_misses += 1,
+      }
+      latest.add(position, 1);
+    }
+
+    Self {
+      histogram,
+      cold_misses,
+      references,
+    }
+  }
+
+  /// Number of distinct pages of the trace; LRU only takes cold misses from this many frames on
+  pub fn distinct_pages(&self) -> usize {
+    self.cold_misses
+  }
+
+  /// LRU faults for every frame count from 1 to `max_frames`
+  pub fn faults(&self, max_frames: usize) -> Vec<usize> {
+    // references with a distance above `frames` fault, walk down from the deepest one
+    let mut deeper = self.histogram.iter().sum::<usize>();
+    (1..=max_frames)
+      .map(|frames| {
+        deeper -= self.histogram.get(frames).copied().unwrap_or(0);
+        self.cold_misses + deeper
+      })
+      .collect()
+  }
+}
+
+pub fn entrypoint(
+  MrcOptions {
+    page_table_size,
+    max_frames,
+    trace: trace_file,
+    output,
+  }: &MrcOptions,
+) -> anyhow::Result<String> {
+  let trace = Trace::from_file(trace_file)?;
+  let distances = StackDistances::from_trace(&trace, *page_table_size);
+  let max_frames = max_frames.unwrap_or_else(|| distances.distinct_pages().max(1));
+
+  let reports = distances
+    .faults(max_frames)
+    .into_iter()
+    .zip(1..)
+    .map(|(misses, frames)| Report {
+      algorithm: PALAlgorithm::LRU.to_string(),
+      trace: trace_file.clone(),
+      page_size: *page_table_size,
+      frames,
+      references: distances.references,
+      hits: distances.references - misses,
+      misses,
+      // memory fills up with the first `frames` faults, every later one evicts
+      evictions: misses.saturating_sub(frames),
+      dirty_evictions: None,
+      fault_rate: match distances.references {
+        0 => 0.0,
+        references => misses as f64 / references as f64,
+      },
+      seeds: None,
+      page_table: None,
+      swap: None,
+      working_set: None,
+      tlb: None,
+    })
+    .collect::<Vec<_>>();
+
+  report::render_all(&reports, output)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::{Rng, SeedableRng};
+
+  use crate::simulator::Simulator;
+
+  use super::*;
+
+  #[test]
+  fn textbook() {
+    // Belady's string: LRU takes 10 faults with 3 frames and 8 with 4
+    let distances = StackDistances::from_pages([1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5].into_iter());
+
+    assert_eq!(distances.cold_misses, 5);
+    assert_eq!(distances.histogram, vec![0, 0, 0, 2, 2, 3]);
+    assert_eq!(distances.faults(6), vec![12, 12, 10, 8, 5, 5]);
+  }
+
+  #[test]
+  fn agrees_with_lru() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    let addresses = (0..5000)
+      .map(|_| {
+        // a hot set and a cold set of pages, so the curve has some shape
+        let page = match rng.gen_bool(0.8) {
+          true => rng.gen_range(0..12),
+          false => rng.gen_range(0..64),
+        };
+        format!("{:x}", (page << 12) | rng.gen_range(0..4096))
+      })
+      .collect::<Vec<_>>();
+
+    let mut trace = Trace::new();
+    addresses
+      .iter()
+      .for_each(|address| trace.add(address.parse().unwrap()));
+
+    let distances = StackDistances::from_trace(&trace, 4096);
+    let max_frames = distances.distinct_pages() + 2;
+
+    for (frames, faults) in (1..).zip(distances.faults(max_frames)) {
+      let mut simulator = Simulator::new(PALAlgorithm::LRU, frames, 4096);
+      simulator.run(&trace);
+      assert_eq!(faults, simulator.statistics.misses, "{frames} frames");
+    }
+  }
+}
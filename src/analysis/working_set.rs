@@ -0,0 +1,88 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Denning's working set W(t, tau): the distinct pages referenced over the last `tau` references.
+/// Its size is sampled every `tau` references.
+#[derive(Debug, Clone)]
+pub struct WorkingSet {
+  pub tau: usize,
+  /// Last reference to every page of the window
+  pub last_use: HashMap<usize, usize>,
+  /// References of the window, oldest first
+  pub window: VecDeque<(usize, usize)>,
+  pub now: usize,
+  pub samples: Vec<usize>,
+  /// Sum and maximum of the size over every reference
+  pub total: usize,
+  pub max: usize,
+}
+
+impl WorkingSet {
+  pub fn new(tau: usize) -> Self {
+    Self {
+      tau,
+      last_use: HashMap::new(),
+      window: VecDeque::new(),
+      now: 0,
+      samples: Vec::new(),
+      total: 0,
+      max: 0,
+    }
+  }
+
+  pub fn size(&self) -> usize {
+    self.last_use.len()
+  }
+
+  pub fn reference(&mut self, page: usize) {
+    self.now += 1;
+    self.window.push_back((self.now, page));
+    self.last_use.insert(page, self.now);
+
+    while let Some(&(time, page)) = self.window.front() {
+      if time + self.tau > self.now {
+        break;
+      }
+      self.window.pop_front();
+      // only the latest reference to a page takes it out of the window
+      if self.last_use.get(&page) == Some(&time) {
+        self.last_use.remove(&page);
+      }
+    }
+
+    self.total += self.size();
+    self.max = self.max.max(self.size());
+    if self.now.is_multiple_of(self.tau) {
+      self.samples.push(self.size());
+    }
+  }
+
+  pub fn mean(&self) -> f64 {
+    match self.now {
+      0 => 0.0,
+      now => self.total as f64 / now as f64,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn window() {
+    let mut working_set = WorkingSet::new(3);
+    let sizes = [1, 2, 1, 3, 3, 4, 1, 1]
+      .iter()
+      .map(|page| {
+        working_set.reference(*page);
+        working_set.size()
+      })
+      .collect::<Vec<_>>();
+
+    // {1}, {1, 2}, {1, 2}, {2, 1, 3}, {1, 3}, {3, 4}, {3, 4, 1}, {4, 1}
+    assert_eq!(sizes, vec![1, 2, 2, 3, 2, 2, 3, 2]);
+    assert_eq!(working_set.samples, vec![2, 2]);
+    assert_eq!(working_set.max, 3);
+    assert_eq!(working_set.mean(), 17.0 / 8.0);
+  }
+}
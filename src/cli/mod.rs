@@ -1,3 +1,6 @@
+pub mod mrc;
+pub mod pal;
+pub mod sweep;
 pub mod translate;
 
 use clap::{Parser, Subcommand, ValueEnum};
@@ -13,6 +16,10 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
   Translate(translate::TranslateOptions),
+  /// Simulate every combination of traces, algorithms and frame counts
+  Sweep(sweep::SweepOptions),
+  /// LRU fault curve for every frame count, from a single pass over the trace
+  Mrc(mrc::MrcOptions),
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -20,4 +27,5 @@ pub enum Output {
   Text,
   Json,
   Table,
+  Csv,
 }
@@ -0,0 +1,22 @@
+use clap::Args;
+
+use super::Output;
+
+#[derive(Args)]
+pub struct MrcOptions {
+  /// The Page Table size in bits
+  #[arg(long, default_value = "4096")]
+  pub page_table_size: usize,
+
+  /// Largest frame count of the curve, defaults to the number of distinct pages of the trace
+  #[arg(long)]
+  pub max_frames: Option<usize>,
+
+  /// The trace file
+  #[arg(default_value = "/dev/stdin")]
+  pub trace: String,
+
+  /// Output format
+  #[arg(long, default_value = "csv")]
+  pub output: Output,
+}
@@ -0,0 +1,56 @@
+use clap::{builder::RangedU64ValueParser, Args};
+
+use crate::pal::{script::ScriptPolicy, PALAlgorithm};
+
+/// Parameters of the PAL algorithms that take any
+#[derive(Args, Debug, Clone)]
+pub struct PALOptions {
+  /// Width of the aging shift registers
+  #[arg(long, default_value = "8", value_parser = clap::value_parser!(u32).range(1..=64))]
+  pub aging_bits: u32,
+
+  /// References between two shifts of the aging registers
+  #[arg(long, default_value = "1", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+  pub aging_interval: usize,
+
+  /// References between two halvings of the LFU with decay counters
+  #[arg(long, default_value = "1000", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+  pub decay_interval: usize,
+
+  /// Window of the working set, in references
+  #[arg(long, default_value = "1000", value_parser = clap::value_parser!(u64).range(1..))]
+  pub tau: u64,
+
+  /// Seed of the random replacement, runs with the same seed evict the same frames
+  #[arg(long, default_value = "0")]
+  pub seed: u64,
+
+  /// Rhai script of the `script` algorithm, defining `choose_victim(resident)` and optionally
+  /// `init`, `on_hit`, `on_fault`, `on_evict` and `on_load`
+  #[arg(long)]
+  pub policy_file: Option<ScriptPolicy>,
+}
+
+impl Default for PALOptions {
+  fn default() -> Self {
+    Self {
+      aging_bits: 8,
+      aging_interval: 1,
+      decay_interval: 1000,
+      tau: 1000,
+      seed: 0,
+      policy_file: None,
+    }
+  }
+}
+
+impl PALOptions {
+  /// Checks the options every algorithm of the run needs are there
+  pub fn check(&self, algorithms: &[PALAlgorithm]) -> anyhow::Result<()> {
+    anyhow::ensure!(
+      self.policy_file.is_some() || !algorithms.contains(&PALAlgorithm::Script),
+      "The script algorithm needs its --policy-file"
+    );
+    Ok(())
+  }
+}
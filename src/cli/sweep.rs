@@ -0,0 +1,75 @@
+use std::str::FromStr;
+
+use clap::{builder::RangedU64ValueParser, Args};
+
+use crate::pal::{PALAlgorithm, PALAlgorithmParser};
+
+use super::{pal::PALOptions, Output};
+
+/// Frame counts given as a single value (`64`) or as `A..B`, every power of two from A to B
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameCounts(pub Vec<usize>);
+
+impl FromStr for FrameCounts {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    // no policy can run without a frame
+    let parse = |value: &str| match value.trim().parse::<usize>() {
+      Ok(count) if count > 0 => Ok(count),
+      _ => Err(format!("Invalid frame count: {}", value)),
+    };
+
+    match s.split_once("..") {
+      Some((from, to)) => {
+        let (from, to) = (parse(from)?, parse(to)?);
+        if !from.is_power_of_two() || from > to {
+          return Err(format!("Invalid frame range: {}", s));
+        }
+
+        Ok(Self(
+          std::iter::successors(Some(from), |x| x.checked_mul(2))
+            .take_while(|x| *x <= to)
+            .collect(),
+        ))
+      }
+      None => Ok(Self(vec![parse(s)?])),
+    }
+  }
+}
+
+#[derive(Args)]
+pub struct SweepOptions {
+  /// The Page Table size in bits
+  #[arg(long, default_value = "4096")]
+  pub page_table_size: usize,
+
+  /// Frame counts to simulate, comma separated. `A..B` expands to every power of two from A to B
+  #[arg(long, value_delimiter = ',', default_value = "1..65536")]
+  pub frames: Vec<FrameCounts>,
+
+  /// Algorithms to the PAL system, comma separated, `plugin:<path>` for a policy of a dynamic
+  /// library
+  #[arg(long, value_delimiter = ',', default_value = "lru", value_parser = PALAlgorithmParser)]
+  pub algorithm: Vec<PALAlgorithm>,
+
+  /// The trace files
+  #[arg(required = true)]
+  pub traces: Vec<String>,
+
+  /// Number of simulations running at the same time, defaults to the available parallelism
+  #[arg(long)]
+  pub jobs: Option<usize>,
+
+  #[command(flatten)]
+  pub pal: PALOptions,
+
+  /// Runs of every seeded algorithm, with the seeds from `--seed` on. More than one reports the
+  /// mean and standard deviation of the misses
+  #[arg(long, default_value = "1", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+  pub seeds: usize,
+
+  /// Output format
+  #[arg(long, default_value = "csv")]
+  pub output: Output,
+}
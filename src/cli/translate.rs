@@ -1,8 +1,11 @@
-use clap::Args;
+use clap::{builder::RangedU64ValueParser, Args};
 
-use crate::pal::PALAlgorithm;
+use crate::{
+  mmu::page_table::PageTableLayout,
+  pal::{PALAlgorithm, PALAlgorithmParser},
+};
 
-use super::Output;
+use super::{pal::PALOptions, Output};
 
 #[derive(Args)]
 pub struct TranslateOptions {
@@ -11,17 +14,48 @@ pub struct TranslateOptions {
   pub page_table_size: usize,
 
   /// The PAL Table entries
-  #[arg(long, default_value = "4096")]
+  #[arg(long, default_value = "4096", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
   pub pal_table_entries: usize,
 
-  /// Algorithm to the PAL system
-  #[arg(long, default_value = "lru")]
+  /// Algorithm to the PAL system, `plugin:<path>` for a policy of a dynamic library
+  #[arg(long, default_value = "lru", value_parser = PALAlgorithmParser)]
   pub algorithm: PALAlgorithm,
 
   /// The trace file
   #[arg(default_value = "/dev/stdin")]
   pub trace: String,
 
+  /// Layout of the page table
+  #[arg(long, default_value = "flat")]
+  pub page_table: PageTableLayout,
+
+  /// Bits of the logical addresses
+  #[arg(long, default_value = "32")]
+  pub address_bits: usize,
+
+  /// Bits of the page number indexing every level of the page table, root first, comma separated
+  #[arg(long, value_delimiter = ',')]
+  pub level_bits: Vec<usize>,
+
+  /// TLB entries, no TLB is simulated when 0
+  #[arg(long, default_value = "0")]
+  pub tlb_entries: usize,
+
+  /// TLB associativity, defaults to a fully associative TLB
+  #[arg(long)]
+  pub tlb_ways: Option<usize>,
+
+  /// Algorithm replacing entries within a TLB set
+  #[arg(long, default_value = "lru", value_parser = PALAlgorithmParser)]
+  pub tlb_algorithm: PALAlgorithm,
+
+  #[command(flatten)]
+  pub pal: PALOptions,
+
+  /// Log every reference to stderr, with the adaptive state of the algorithm
+  #[arg(long, short)]
+  pub verbose: bool,
+
   /// Output format
   #[arg(long, default_value = "text")]
   pub output: Output,
@@ -0,0 +1,23 @@
+use std::sync::{
+  atomic::{AtomicU64, Ordering},
+  Arc,
+};
+
+/// Logical simulation clock. It advances by one tick per memory reference, so every policy that
+/// reasons about time sees the same, deterministic timeline regardless of how fast the host is.
+///
+/// Cloning a `Clock` yields a handle to the same timeline.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+  ticks: Arc<AtomicU64>,
+}
+
+impl Clock {
+  pub fn now(&self) -> u64 {
+    self.ticks.load(Ordering::Relaxed)
+  }
+
+  pub fn tick(&self) -> u64 {
+    self.ticks.fetch_add(1, Ordering::Relaxed) + 1
+  }
+}
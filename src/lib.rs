@@ -1,4 +1,9 @@
+pub mod analysis;
 pub mod cli;
+pub mod clock;
 pub mod mmu;
 pub mod memory;
 pub mod pal;
+pub mod report;
+pub mod simulator;
+pub mod sweep;
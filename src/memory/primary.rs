@@ -1,22 +1,20 @@
-use std::sync::Mutex;
-
-use singleton_manager::sm;
-
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Frame {
   pub data: bool,
 }
 
+#[derive(Debug, Clone)]
 pub struct PrimaryMemory {
   pub frames: Vec<Frame>,
-  pub guard: Mutex<()>, // resource aquisition is initialization (RAII)
+  /// No frame below this index is free
+  pub first_free: usize,
 }
 
 impl PrimaryMemory {
   pub fn new(size: usize) -> Self {
     Self {
       frames: (0..size).map(|_| Frame::default()).collect(),
-      guard: Mutex::new(()),
+      first_free: 0,
     }
   }
 
@@ -25,25 +23,20 @@ impl PrimaryMemory {
   }
 
   pub fn alloc_frame(&mut self) -> Option<usize> {
-    let _guard = self.guard.lock().expect("Failed to get guard");
-
-    for (i, frame) in self.frames.iter_mut().enumerate() {
+    for (i, frame) in self.frames.iter_mut().enumerate().skip(self.first_free) {
       if !frame.data {
         frame.data = true;
+        self.first_free = i + 1;
         return Some(i);
       }
     }
 
+    self.first_free = self.frames.len();
     None
   }
 
-  pub fn get() -> &'static mut Self {
-    sm().get::<Self>("MEMORY").unwrap()
-  }
-
-  pub fn create(size: usize) {
-    sm()
-      .set("MEMORY", PrimaryMemory::new(size))
-      .expect("Failed to create memory");
+  pub fn free_frame(&mut self, index: usize) {
+    self.frames[index].data = false;
+    self.first_free = self.first_free.min(index);
   }
 }
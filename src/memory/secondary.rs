@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+/// How a page fault got the page contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+  /// First touch, or the page never left memory dirty: a zeroed frame will do
+  ZeroFill,
+  /// The page was read back from its swap slot
+  PageIn,
+}
+
+/// Swap device. Pages evicted dirty are written to a slot, and read back from it when they fault
+/// again. A slot is released as soon as the page is dirtied in memory, its copy being stale.
+#[derive(Debug, Clone, Default)]
+pub struct SecondaryMemory {
+  /// Slot holding the copy of every swapped page
+  pub slots: HashMap<usize, usize>,
+  /// Released slots, reused before allocating new ones
+  pub free_slots: Vec<usize>,
+  /// Slots allocated so far, the size the swap device had to grow to
+  pub allocated: usize,
+  /// Allocations served by a released slot
+  pub reused: usize,
+  pub page_ins: usize,
+  pub page_outs: usize,
+  pub zero_fills: usize,
+}
+
+impl SecondaryMemory {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Swap slot of `page`, if it has an up to date copy
+  pub fn slot(&self, page: usize) -> Option<usize> {
+    self.slots.get(&page).copied()
+  }
+
+  fn alloc_slot(&mut self) -> usize {
+    match self.free_slots.pop() {
+      Some(slot) => {
+        self.reused += 1;
+        slot
+      }
+      None => {
+        self.allocated += 1;
+        self.allocated - 1
+      }
+    }
+  }
+
+  /// Evicts `page`: a dirty page is written to a slot, a clean one is already there or zeroed
+  pub fn page_out(&mut self, page: usize, dirty: bool) {
+    if dirty {
+      let slot = match self.slot(page) {
+        Some(slot) => slot,
+        None => self.alloc_slot(),
+      };
+      self.slots.insert(page, slot);
+      self.page_outs += 1;
+    }
+  }
+
+  /// Loads `page` on a fault, from its slot if it has one
+  pub fn page_in(&mut self, page: usize) -> FaultKind {
+    match self.slots.contains_key(&page) {
+      true => {
+        self.page_ins += 1;
+        FaultKind::PageIn
+      }
+      false => {
+        self.zero_fills += 1;
+        FaultKind::ZeroFill
+      }
+    }
+  }
+
+  /// `page` was modified in memory, so its swapped copy is stale and the slot can be reused
+  pub fn discard(&mut self, page: usize) {
+    if let Some(slot) = self.slots.remove(&page) {
+      self.free_slots.push(slot);
+    }
+  }
+
+  /// Slots currently holding a page
+  pub fn used(&self) -> usize {
+    self.slots.len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn swap_slots() {
+    let mut swap = SecondaryMemory::new();
+    assert_eq!(swap.page_in(1), FaultKind::ZeroFill);
+
+    // a clean page is not written out, so it is zero filled again
+    swap.page_out(1, false);
+    assert_eq!(swap.page_in(1), FaultKind::ZeroFill);
+
+    swap.page_out(1, true);
+    swap.page_out(2, true);
+    assert_eq!((swap.slot(1), swap.slot(2)), (Some(0), Some(1)));
+    assert_eq!(swap.page_in(1), FaultKind::PageIn);
+
+    // evicting it clean keeps the copy in the same slot
+    swap.page_out(1, false);
+    assert_eq!(swap.page_in(1), FaultKind::PageIn);
+
+    // once written, its slot goes to the next page written out
+    swap.discard(1);
+    swap.page_out(3, true);
+    assert_eq!(swap.slot(3), Some(0));
+    assert_eq!(swap.slot(1), None);
+
+    assert_eq!((swap.page_ins, swap.page_outs, swap.zero_fills), (2, 3, 2));
+    assert_eq!((swap.allocated, swap.reused, swap.used()), (2, 1, 2));
+  }
+}
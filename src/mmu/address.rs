@@ -1,9 +1,40 @@
 use std::str::FromStr;
 
+/// What a reference does with the address, given by an optional `R`, `W` or `X` trace prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AccessKind {
+  /// Bare addresses are taken as reads: traces without prefixes say nothing of writes, so their
+  /// pages are never dirty
+  #[default]
+  Read,
+  Write,
+  Execute,
+}
+
+impl AccessKind {
+  pub fn is_write(&self) -> bool {
+    *self == AccessKind::Write
+  }
+}
+
+impl FromStr for AccessKind {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "R" | "r" => Ok(AccessKind::Read),
+      "W" | "w" => Ok(AccessKind::Write),
+      "X" | "x" => Ok(AccessKind::Execute),
+      _ => anyhow::bail!("Unknown access type {s}, expected R, W or X"),
+    }
+  }
+}
+
 // TODO: Check if we can `instantiate` one of this kind of struct with the determinated size of the
 // page.
 pub struct LogicalAddress {
   pub value: u64,
+  pub access: AccessKind,
 }
 
 impl LogicalAddress {
@@ -30,18 +61,45 @@ impl LogicalAddress {
   }
 }
 
+/// Parses `2004f8` or, with an access type, `W 2004f8`
 impl FromStr for LogicalAddress {
-  type Err = std::num::ParseIntError;
+  type Err = anyhow::Error;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    let value_in_hex = u64::from_str_radix(s.trim(), 16)
-      .unwrap_or_else(|_| panic!("Failed to parse address {}", s));
+    let (access, address) = match s.split_whitespace().collect::<Vec<_>>()[..] {
+      [address] => (AccessKind::default(), address),
+      [access, address] => (access.parse()?, address),
+      _ => anyhow::bail!("Failed to parse address {s}"),
+    };
+
+    let value_in_hex = u64::from_str_radix(address, 16)
+      .map_err(|error| anyhow::anyhow!("Failed to parse address {address}: {error}"))?;
 
     // print the value in binary with 32 bits
     // println!("{:032b}", value_in_hex);
 
     Ok(LogicalAddress {
       value: value_in_hex,
+      access,
     })
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn access_kinds() {
+    let address = LogicalAddress::from_str("  2004f8").unwrap();
+    assert_eq!((address.value, address.access), (0x2004f8, AccessKind::Read));
+
+    let address = LogicalAddress::from_str("R 10003c").unwrap();
+    assert_eq!((address.value, address.access), (0x10003c, AccessKind::Read));
+    assert_eq!(LogicalAddress::from_str("x 1").unwrap().access, AccessKind::Execute);
+
+    assert!(LogicalAddress::from_str("M 10003c").is_err());
+    assert!(LogicalAddress::from_str("W").is_err());
+    assert!(LogicalAddress::from_str("W 1 2").is_err());
+  }
+}
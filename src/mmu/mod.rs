@@ -1,172 +1,227 @@
-use crate::{cli::translate::TranslateOptions, memory::primary::PrimaryMemory, pal::PAL};
+use crate::{
+  memory::{
+    primary::PrimaryMemory,
+    secondary::{FaultKind, SecondaryMemory},
+  },
+  pal::PAL,
+};
 use std::str::FromStr;
 
 use self::{
-  address::LogicalAddress,
-  page_table::{PageTable, PageTableEntry},
+  address::{AccessKind, LogicalAddress},
+  page_table::{flat::FlatPageTable, PageTable},
+  tlb::TLB,
   trace::Trace,
 };
 
 pub mod address;
 pub mod page_table;
+pub mod tlb;
 pub mod trace;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TranslationResult {
-  Fault,
+  /// The page was not mapped, and `kind` says how it got its contents. `evicted` is the frame
+  /// reclaimed to load it, if memory was full
+  Fault {
+    kind: FaultKind,
+    evicted: Option<usize>,
+  },
   Hit,
 }
 
 #[derive(Debug, Clone)]
 pub struct MMU {
-  pub page_table: PageTable,
+  pub page_table: Box<dyn PageTable>,
   pub page_size: usize,
+  pub tlb: Option<TLB>,
+  /// Page table walks, one per translation the TLB could not serve
+  pub walks: usize,
+  /// Tables visited over all the walks
+  pub walk_depth: usize,
+  /// Evictions of modified pages, which had to be written back
+  pub dirty_evictions: usize,
+  /// Frames the PAL released without a fault asking for them
+  pub released: usize,
 }
 
 impl MMU {
   pub fn new(page_size: usize) -> Self {
+    Self::with_page_table(
+      Box::new(FlatPageTable::new(
+        1 << (32 - page_size.ilog2()),
+        4,
+        page_size,
+      )),
+      page_size,
+    )
+  }
+
+  pub fn with_page_table(page_table: Box<dyn PageTable>, page_size: usize) -> Self {
     Self {
-      page_table: PageTable {
-        entries: (0..(1 << (32 - page_size.ilog2())))
-          .map(|_| PageTableEntry::default())
-          .collect(),
-      },
+      page_table,
       page_size,
+      tlb: None,
+      walks: 0,
+      walk_depth: 0,
+      dirty_evictions: 0,
+      released: 0,
     }
   }
 
-  pub fn translate(&mut self, address: &LogicalAddress) -> TranslationResult {
+  pub fn translate(
+    &mut self,
+    address: &LogicalAddress,
+    memory: &mut PrimaryMemory,
+    swap: &mut SecondaryMemory,
+    pal: &mut PAL,
+  ) -> TranslationResult {
     let (page, _offset) = address.split(self.page_size);
     // println!("Page: {}, Offset: {}", page, offset);
 
-    let res = match self.page_table.get_frame(page) {
+    if let Some(frame) = self.tlb.as_mut().and_then(|tlb| tlb.lookup(page)) {
+      pal.on_hit(page, address.access, frame);
+      self.reference(page, address.access, swap);
+
+      return TranslationResult::Hit;
+    }
+
+    let walk = self.page_table.walk(page);
+    self.walks += 1;
+    self.walk_depth += walk.depth;
+
+    let res = match walk.frame {
       Some(frame) => {
-        PAL::get().insert(frame);
+        pal.on_hit(page, address.access, frame);
+        self.reference(page, address.access, swap);
 
         TranslationResult::Hit
       }
       None => {
-        match PrimaryMemory::get().alloc_frame() {
+        let kind = swap.page_in(page);
+        pal.on_fault(page, address.access);
+
+        let evicted = match memory.alloc_frame() {
           Some(frame) => {
             // 1. Insert page table
             self.page_table.set_frame(page, frame);
 
-            // 2. Send frame to PAL
-            PAL::get().insert(frame);
+            // 2. Tell the PAL where the page went
+            pal.on_load(frame);
+
+            None
           }
           None => {
-            // 1. PAL find the frame to deallocate, remove it from the PAL entries and return it 
-            let frame = PAL::get().find_frame_to_deallocate();
-
-            // 2. Invalidate the page table entry
-            self.page_table.invalidate_frame(frame);
+            // 1. PAL chooses the frame to deallocate, writing pages back on the way
+            let frame = pal.choose_victim();
+            while let Some(frame) = pal.write_back() {
+              self.clean(frame, swap);
+            }
+
+            // 2. Write the page out if needed, invalidate the page table entry and its cached
+            // translation, then let the PAL forget the frame
+            if self.unmap(frame, swap) {
+              self.dirty_evictions += 1;
+            }
+            pal.on_evict(frame);
 
             // 3. Insert page table
             self.page_table.set_frame(page, frame);
 
-            // 4. Send frame to PAL
-            PAL::get().insert(frame);
+            // 4. Tell the PAL where the page went
+            pal.on_load(frame);
+
+            Some(frame)
           }
-        }
-        TranslationResult::Fault
+        };
+        self.reference(page, address.access, swap);
+        TranslationResult::Fault { kind, evicted }
       }
     };
-    // PAL::get().print();
-    res
-  }
 
-  pub fn translate_str(&mut self, address: &str) -> anyhow::Result<()> {
-    self.translate(&LogicalAddress::from_str(address)?);
+    if let (Some(tlb), Some(frame)) = (self.tlb.as_mut(), self.page_table.get_frame(page)) {
+      tlb.fill(page, frame);
+    }
 
-    Ok(())
+    // frames the PAL gives back on its own, as the working set shrinks
+    while let Some(frame) = pal.release() {
+      self.unmap(frame, swap);
+      pal.on_evict(frame);
+      memory.free_frame(frame);
+      self.released += 1;
+    }
+    // pal.print();
+    res
   }
-}
 
-impl Default for MMU {
-  fn default() -> Self {
-    Self::new(4096)
+  /// Fails for the first address of `trace` whose page the table cannot map, `name` being the
+  /// file the trace was read from
+  pub fn check(&self, name: &str, trace: &Trace) -> anyhow::Result<()> {
+    for address in trace.iter() {
+      if let Err(error) = self.page_table.check(address.page(self.page_size)) {
+        anyhow::bail!("{name}: address {:x}: {error}", address.value);
+      }
+    }
+    Ok(())
   }
-}
 
-pub fn entrypoint(
-  TranslateOptions {
-    output: _,
-    trace: trace_file,
-    page_table_size,
-    algorithm,
-    pal_table_entries,
-  }: &TranslateOptions,
-) -> anyhow::Result<Vec<TranslationResult>> {
-  PrimaryMemory::create(*pal_table_entries);
-  PAL::create(*algorithm, *pal_table_entries);
-  let mut mmu = MMU::new(*page_table_size);
-  let trace = Trace::from_file(trace_file)?;
-
-  Ok(
-    trace
-      .into_iter()
-      .map(|address| mmu.translate(&address))
-      .collect(),
-  )
-}
+  /// Takes the page out of `frame`: writes it to swap if dirty, and drops its page table entry
+  /// and cached translation. Returns whether it was dirty.
+  fn unmap(&mut self, frame: usize, swap: &mut SecondaryMemory) -> bool {
+    let Some(page) = self.page_table.page_of(frame) else {
+      return false;
+    };
 
-#[cfg(test)]
-mod tests {
-  use rand::Rng;
+    let dirty = self
+      .page_table
+      .entry_mut(page)
+      .is_some_and(|entry| entry.dirty);
+    swap.page_out(page, dirty);
+    if let Some(tlb) = self.tlb.as_mut() {
+      tlb.invalidate(page);
+    }
+    self.page_table.invalidate_frame(frame);
 
-  use crate::pal::PALAlgorithm;
+    dirty
+  }
 
-  use super::*;
-  #[test]
-  fn test_mmu() {
-    let mmu = MMU::new(4096);
-    PrimaryMemory::create(4096);
-    PAL::create(PALAlgorithm::LRU, 4096);
+  /// Writes the page in `frame` to swap if dirty, leaving it mapped and clean
+  fn clean(&mut self, frame: usize, swap: &mut SecondaryMemory) {
+    let Some(page) = self.page_table.page_of(frame) else {
+      return;
+    };
 
-    assert_eq!(mmu.page_size, 4096);
-    assert_eq!(mmu.page_table.entries.len(), 4096u32.ilog2() as usize);
+    if let Some(entry) = self.page_table.entry_mut(page).filter(|entry| entry.dirty) {
+      entry.dirty = false;
+      swap.page_out(page, true);
+    }
   }
 
-  #[test]
-  fn test_translate() {
-    let mut mmu = MMU::new(4096);
-    PrimaryMemory::create(4096);
-    PAL::create(PALAlgorithm::LRU, 4096);
-
-    mmu.translate_str("345678").unwrap();
-    mmu.translate_str("345678").unwrap();
+  /// Sets the referenced bit of `page`, and on writes the dirty one. The first write also makes the
+  /// swapped copy of the page stale.
+  fn reference(&mut self, page: usize, access: AccessKind, swap: &mut SecondaryMemory) {
+    if let Some(entry) = self.page_table.entry_mut(page) {
+      entry.referenced = true;
+      if access.is_write() && !entry.dirty {
+        entry.dirty = true;
+        swap.discard(page);
+      }
+    }
   }
 
-  #[test]
-  fn manually() {
-    let mut mmu = MMU::new(4096);
-    PrimaryMemory::create(4096);
-    PAL::create(PALAlgorithm::LRU, 4096);
-
-    ["001123", "002123", "001123", "003123", "005123", "006123"]
-      .iter()
-      .for_each(|address| {
-        mmu.translate_str(address).unwrap();
-      });
+  pub fn translate_str(
+    &mut self,
+    address: &str,
+    memory: &mut PrimaryMemory,
+    swap: &mut SecondaryMemory,
+    pal: &mut PAL,
+  ) -> anyhow::Result<TranslationResult> {
+    Ok(self.translate(&LogicalAddress::from_str(address)?, memory, swap, pal))
   }
+}
 
-  #[test]
-  fn complex() {
-    let mut mmu = MMU::new(4096);
-    PrimaryMemory::create(4096);
-    PAL::create(PALAlgorithm::LRU, 4096);
-
-    // Generate random addresses
-    let addresses = (0..10000)
-      .map(|_| {
-        let mut rng = rand::thread_rng();
-        let address: u32 = rng.gen();
-        format!("{:06x}", address)
-      })
-      .collect::<Vec<String>>();
-
-    addresses.iter().for_each(|address| {
-      mmu.translate_str(address).unwrap();
-    });
+impl Default for MMU {
+  fn default() -> Self {
+    Self::new(4096)
   }
 }
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use super::{PageTable, PageTableEntry, Walk};
+
+/// Single level page table, one entry for every page of the address space
+#[derive(Debug, Clone)]
+pub struct FlatPageTable {
+  pub entries: Vec<PageTableEntry>,
+  /// Page currently mapped to each frame, so a frame can be invalidated without a full scan
+  pub frame_pages: HashMap<usize, usize>,
+  /// Bytes taken by every entry
+  pub entry_size: usize,
+  pub page_size: usize,
+}
+
+impl FlatPageTable {
+  pub fn new(size: usize, entry_size: usize, page_size: usize) -> Self {
+    Self {
+      entries: (0..size).map(|_| PageTableEntry::default()).collect(),
+      frame_pages: HashMap::new(),
+      entry_size,
+      page_size,
+    }
+  }
+
+  pub fn print_valid_frames(&self) {
+    self.entries.iter().enumerate().for_each(|(index, entry)| {
+      if entry.valid {
+        println!("Page {index} is valid", index = index);
+      }
+    });
+  }
+}
+
+impl PageTable for FlatPageTable {
+  fn walk(&self, index: usize) -> Walk {
+    let entry = self.entries[index];
+
+    Walk {
+      frame: match entry.valid {
+        true => Some(entry.page_frame_index),
+        false => None,
+      },
+      depth: 1,
+    }
+  }
+
+  fn set_frame(&mut self, index: usize, frame: usize) {
+    self.invalidate(index);
+    self.frame_pages.insert(frame, index);
+    self.entries[index] = PageTableEntry {
+      page_frame_index: frame,
+      valid: true,
+      ..Default::default()
+    }
+  }
+
+  fn invalidate(&mut self, index: usize) {
+    let entry = self.entries[index];
+    if entry.valid && self.frame_pages.get(&entry.page_frame_index) == Some(&index) {
+      self.frame_pages.remove(&entry.page_frame_index);
+    }
+
+    self.entries[index] = PageTableEntry::default();
+  }
+
+  fn invalidate_frame(&mut self, frame: usize) {
+    if let Some(index) = self.frame_pages.remove(&frame) {
+      self.entries[index].valid = false;
+    }
+  }
+
+  fn entry_mut(&mut self, page: usize) -> Option<&mut PageTableEntry> {
+    self.entries.get_mut(page).filter(|entry| entry.valid)
+  }
+
+  fn page_of(&self, frame: usize) -> Option<usize> {
+    self.frame_pages.get(&frame).copied()
+  }
+
+  fn pages(&self) -> usize {
+    self.entries.len()
+  }
+
+  fn table_pages(&self) -> usize {
+    (self.entries.len() * self.entry_size).div_ceil(self.page_size)
+  }
+
+  fn levels(&self) -> Vec<usize> {
+    vec![self.entries.len().ilog2() as usize]
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PageTable> {
+    Box::new(self.clone())
+  }
+}
@@ -0,0 +1,160 @@
+use super::{PageTable, PageTableEntry, PageTableLayout, Walk};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvertedPageTableEntry {
+  pub pid: usize,
+  pub page: usize,
+  /// Frame number and control bits, as a forward table would hold them
+  pub pte: PageTableEntry,
+  /// Next frame whose page hashes to the same anchor
+  pub next: Option<usize>,
+}
+
+/// Inverted page table: one entry per physical frame, naming the page it holds. Lookups hash
+/// `(pid, page)` into the anchor table and follow the chain of frames sharing that anchor.
+#[derive(Debug, Clone)]
+pub struct InvertedPageTable {
+  pub entries: Vec<Option<InvertedPageTableEntry>>,
+  /// First frame of the chain of every hash bucket
+  pub anchors: Vec<Option<usize>>,
+  /// Process the translations belong to, traces carry a single one
+  pub pid: usize,
+  pub page_bits: usize,
+  /// Bytes taken by a page number, an entry holds a tag and a chain link of that size
+  pub entry_size: usize,
+  pub page_size: usize,
+}
+
+impl InvertedPageTable {
+  pub fn new(frames: usize, page_bits: usize, entry_size: usize, page_size: usize) -> Self {
+    Self {
+      entries: vec![None; frames],
+      anchors: vec![None; frames.max(1)],
+      pid: 0,
+      page_bits,
+      entry_size,
+      page_size,
+    }
+  }
+
+  fn anchor(&self, page: usize) -> usize {
+    // Fibonacci hashing of the (pid, page) key
+    let key = (page as u64) ^ ((self.pid as u64) << 48);
+    (key.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 16) as usize % self.anchors.len()
+  }
+
+  /// Frames of the chain starting at `anchor`, in chain order
+  fn chain(&self, anchor: usize) -> impl Iterator<Item = usize> + '_ {
+    std::iter::successors(self.anchors[anchor], |&frame| {
+      self.entries[frame].and_then(|entry| entry.next)
+    })
+  }
+
+  fn unlink(&mut self, frame: usize) {
+    let Some(entry) = self.entries[frame].take() else {
+      return;
+    };
+
+    let anchor = self.anchor(entry.page);
+    let previous = self
+      .chain(anchor)
+      .take_while(|&other| other != frame)
+      .last();
+    match previous {
+      Some(previous) => {
+        if let Some(previous) = self.entries[previous].as_mut() {
+          previous.next = entry.next;
+        }
+      }
+      None => self.anchors[anchor] = entry.next,
+    }
+  }
+}
+
+impl PageTable for InvertedPageTable {
+  fn walk(&self, page: usize) -> Walk {
+    // the anchor table itself is the first memory reference
+    let mut depth = 1;
+
+    for frame in self.chain(self.anchor(page)) {
+      depth += 1;
+      if let Some(entry) = self.entries[frame] {
+        if entry.pid == self.pid && entry.page == page {
+          return Walk {
+            frame: Some(frame),
+            depth,
+          };
+        }
+      }
+    }
+
+    Walk { frame: None, depth }
+  }
+
+  fn set_frame(&mut self, page: usize, frame: usize) {
+    self.invalidate(page);
+    self.unlink(frame);
+
+    let anchor = self.anchor(page);
+    self.entries[frame] = Some(InvertedPageTableEntry {
+      pid: self.pid,
+      page,
+      pte: PageTableEntry {
+        page_frame_index: frame,
+        valid: true,
+        ..Default::default()
+      },
+      next: self.anchors[anchor],
+    });
+    self.anchors[anchor] = Some(frame);
+  }
+
+  fn invalidate(&mut self, page: usize) {
+    if let Some(frame) = self.get_frame(page) {
+      self.unlink(frame);
+    }
+  }
+
+  fn invalidate_frame(&mut self, frame: usize) {
+    self.unlink(frame);
+  }
+
+  fn entry_mut(&mut self, page: usize) -> Option<&mut PageTableEntry> {
+    let frame = self.get_frame(page)?;
+    self.entries[frame].as_mut().map(|entry| &mut entry.pte)
+  }
+
+  fn page_of(&self, frame: usize) -> Option<usize> {
+    self.entries[frame].map(|entry| entry.page)
+  }
+
+  fn pages(&self) -> usize {
+    1 << self.page_bits
+  }
+
+  fn table_pages(&self) -> usize {
+    let bytes = self.entries.len() * 2 * self.entry_size + self.anchors.len() * self.entry_size;
+    bytes.div_ceil(self.page_size)
+  }
+
+  fn levels(&self) -> Vec<usize> {
+    vec![]
+  }
+
+  fn layout(&self) -> PageTableLayout {
+    PageTableLayout::Inverted
+  }
+
+  fn chains(&self) -> Option<Vec<usize>> {
+    Some(
+      (0..self.anchors.len())
+        .map(|anchor| self.chain(anchor).count())
+        .filter(|length| *length > 0)
+        .collect(),
+    )
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PageTable> {
+    Box::new(self.clone())
+  }
+}
@@ -0,0 +1,314 @@
+use clap::ValueEnum;
+use core::fmt::{Debug, Display};
+
+use self::{flat::FlatPageTable, inverted::InvertedPageTable, radix::RadixPageTable};
+
+pub mod flat;
+pub mod inverted;
+pub mod radix;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageTableEntry {
+  pub page_frame_index: usize,
+  pub valid: bool,
+  /// Written since the page was loaded, so it must be written back on eviction
+  pub dirty: bool,
+  /// Accessed since the page was loaded
+  pub referenced: bool,
+}
+
+/// Outcome of a page table walk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Walk {
+  pub frame: Option<usize>,
+  /// Tables visited until the frame was found, or until a missing table stopped the walk
+  pub depth: usize,
+}
+
+pub trait PageTable: Debug + Send {
+  fn walk(&self, page: usize) -> Walk;
+  fn set_frame(&mut self, page: usize, frame: usize);
+  fn invalidate(&mut self, page: usize);
+  fn invalidate_frame(&mut self, frame: usize);
+  /// Entry of `page` while it is mapped
+  fn entry_mut(&mut self, page: usize) -> Option<&mut PageTableEntry>;
+  /// Page currently mapped to `frame`
+  fn page_of(&self, frame: usize) -> Option<usize>;
+  /// Pages of the address space the table can map
+  fn pages(&self) -> usize;
+  /// Memory taken by the table itself, in pages
+  fn table_pages(&self) -> usize;
+  /// Bits of the page number indexing every level, root first
+  fn levels(&self) -> Vec<usize>;
+  fn clone_dyn(&self) -> Box<dyn PageTable>;
+
+  /// Fails for a page the table cannot map, beyond its address space
+  fn check(&self, page: usize) -> anyhow::Result<()> {
+    match page < self.pages() {
+      true => Ok(()),
+      false => Err(unmappable(page, self.pages())),
+    }
+  }
+
+  /// Length of every non empty hash chain, for tables looked up through one
+  fn chains(&self) -> Option<Vec<usize>> {
+    None
+  }
+
+  fn get_frame(&self, page: usize) -> Option<usize> {
+    self.walk(page).frame
+  }
+
+  fn layout(&self) -> PageTableLayout {
+    match self.levels().len() {
+      1 => PageTableLayout::Flat,
+      2 => PageTableLayout::TwoLevel,
+      _ => PageTableLayout::Radix,
+    }
+  }
+}
+
+/// Error for a page past the `pages` a table maps
+fn unmappable(page: usize, pages: usize) -> anyhow::Error {
+  anyhow::anyhow!("Page {page:#x} is beyond the {pages} pages of the table")
+}
+
+impl Clone for Box<dyn PageTable> {
+  fn clone(&self) -> Self {
+    self.clone_dyn()
+  }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Hash, Eq, PartialEq, Default)]
+pub enum PageTableLayout {
+  #[default]
+  Flat,
+  TwoLevel,
+  Radix,
+  /// One entry per physical frame, looked up through a hash anchor table
+  Inverted,
+}
+
+impl Display for PageTableLayout {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{}", self.to_possible_value().unwrap().get_name())
+  }
+}
+
+impl PageTableLayout {
+  /// Bits of the page number indexing every level, root first. Two level tables split the page
+  /// number in halves and radix ones in 9 bit levels (as x86-64 does), unless `level_bits` says
+  /// otherwise.
+  pub fn levels(&self, page_bits: usize, level_bits: &[usize]) -> anyhow::Result<Vec<usize>> {
+    let levels = match (self, level_bits) {
+      (PageTableLayout::Flat, []) => vec![page_bits],
+      (PageTableLayout::Flat, _) => anyhow::bail!("A flat page table has a single level"),
+      (PageTableLayout::TwoLevel, []) => vec![page_bits.div_ceil(2), page_bits / 2],
+      (PageTableLayout::TwoLevel, [_, _]) => level_bits.to_vec(),
+      (PageTableLayout::TwoLevel, _) => anyhow::bail!("A two level page table has two levels"),
+      (PageTableLayout::Radix, []) => {
+        let mut levels = vec![9; page_bits / 9];
+        if !page_bits.is_multiple_of(9) {
+          levels.insert(0, page_bits % 9);
+        }
+        levels
+      }
+      (PageTableLayout::Radix, _) => level_bits.to_vec(),
+      (PageTableLayout::Inverted, []) => return Ok(vec![]),
+      (PageTableLayout::Inverted, _) => anyhow::bail!("An inverted page table has no levels"),
+    };
+
+    anyhow::ensure!(
+      levels.iter().sum::<usize>() == page_bits && levels.iter().all(|bits| *bits > 0),
+      "Levels {levels:?} must split the {page_bits} bits of the page number"
+    );
+    Ok(levels)
+  }
+
+  /// Builds the table mapping `address_bits` addresses, an inverted one having an entry for each
+  /// of the `frames` of primary memory
+  pub fn build(
+    &self,
+    address_bits: usize,
+    page_size: usize,
+    level_bits: &[usize],
+    frames: usize,
+  ) -> anyhow::Result<Box<dyn PageTable>> {
+    anyhow::ensure!(
+      page_size.is_power_of_two() && page_size.ilog2() as usize <= address_bits,
+      "The page size must be a power of two no larger than the address space"
+    );
+    anyhow::ensure!(
+      address_bits <= usize::BITS as usize,
+      "Addresses can have at most {} bits",
+      usize::BITS
+    );
+
+    let page_bits = address_bits - page_size.ilog2() as usize;
+    let levels = self.levels(page_bits, level_bits)?;
+    // entries hold a frame number and the control bits, as wide as an address
+    let entry_size = address_bits.div_ceil(8).next_power_of_two();
+
+    Ok(match self {
+      PageTableLayout::Flat => {
+        anyhow::ensure!(
+          page_bits <= 32,
+          "A flat page table for {address_bits} bit addresses would have 2^{page_bits} entries, use a \
+           hierarchical one"
+        );
+        Box::new(FlatPageTable::new(1 << page_bits, entry_size, page_size))
+      }
+      PageTableLayout::TwoLevel | PageTableLayout::Radix => {
+        Box::new(RadixPageTable::new(levels, entry_size, page_size))
+      }
+      PageTableLayout::Inverted => {
+        Box::new(InvertedPageTable::new(frames, page_bits, entry_size, page_size))
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn levels() {
+    assert_eq!(PageTableLayout::Flat.levels(20, &[]).unwrap(), vec![20]);
+    assert_eq!(PageTableLayout::TwoLevel.levels(20, &[]).unwrap(), vec![10, 10]);
+    assert_eq!(PageTableLayout::TwoLevel.levels(21, &[]).unwrap(), vec![11, 10]);
+    assert_eq!(PageTableLayout::TwoLevel.levels(20, &[8, 12]).unwrap(), vec![8, 12]);
+    assert_eq!(PageTableLayout::Radix.levels(36, &[]).unwrap(), vec![9, 9, 9, 9]);
+    assert_eq!(PageTableLayout::Radix.levels(20, &[]).unwrap(), vec![2, 9, 9]);
+    assert!(PageTableLayout::Radix.levels(20, &[10, 9]).is_err());
+    assert!(PageTableLayout::TwoLevel.levels(20, &[5, 5, 10]).is_err());
+    assert!(PageTableLayout::Flat.build(64, 4096, &[], 16).is_err());
+  }
+
+  #[test]
+  fn radix_allocates_on_demand() {
+    let mut table = PageTableLayout::TwoLevel.build(32, 4096, &[], 16).unwrap();
+    // only the root directory, 1024 entries of 4 bytes
+    assert_eq!(table.table_pages(), 1);
+    assert_eq!(table.walk(0x12345), Walk { frame: None, depth: 1 });
+
+    table.set_frame(0x12345, 7);
+    table.set_frame(0x12346, 8);
+    assert_eq!(table.table_pages(), 2);
+    assert_eq!(table.walk(0x12345), Walk { frame: Some(7), depth: 2 });
+    assert_eq!(table.walk(0x12347), Walk { frame: None, depth: 2 });
+
+    table.invalidate_frame(7);
+    assert_eq!(table.get_frame(0x12345), None);
+    assert_eq!(table.page_of(8), Some(0x12346));
+
+    // a 64 bit address space is fine as long as it is hierarchical
+    let mut table = PageTableLayout::Radix.build(64, 4096, &[], 16).unwrap();
+    table.set_frame(0xdead_beef_cafe, 1);
+    assert_eq!(table.levels(), vec![7, 9, 9, 9, 9, 9]);
+    assert_eq!(table.walk(0xdead_beef_cafe), Walk { frame: Some(1), depth: 6 });
+    assert_eq!(table.table_pages(), 6);
+  }
+
+  #[test]
+  fn unmappable_pages() {
+    // pages beyond the address bits are refused, not aliased or indexed past the table
+    for layout in [
+      PageTableLayout::Flat,
+      PageTableLayout::TwoLevel,
+      PageTableLayout::Radix,
+      PageTableLayout::Inverted,
+    ] {
+      let table = layout.build(32, 4096, &[], 16).unwrap();
+      assert!(table.check(0xf_ffff).is_ok());
+      assert_eq!(
+        table.check(0x10_0000).unwrap_err().to_string(),
+        "Page 0x100000 is beyond the 1048576 pages of the table"
+      );
+    }
+
+    let table = PageTableLayout::Radix.build(32, 4096, &[], 16).unwrap();
+    assert_eq!(table.walk(0x10_0000), Walk { frame: None, depth: 1 });
+  }
+
+  #[test]
+  fn layouts_agree() {
+    use crate::{pal::PALAlgorithm, simulator::Simulator};
+
+    let addresses = (0..2000)
+      .map(|i: usize| format!("{:x}", (i * 7919 % 97) << 14 | (i % 4096)))
+      .collect::<Vec<_>>();
+
+    let run = |layout: PageTableLayout| {
+      let mut simulator = Simulator::new(PALAlgorithm::LRU, 16, 4096);
+      simulator.mmu.page_table = layout.build(32, 4096, &[], 16).unwrap();
+      addresses.iter().for_each(|address| {
+        simulator.translate_str(address).unwrap();
+      });
+      simulator.statistics
+    };
+
+    assert_eq!(run(PageTableLayout::Flat), run(PageTableLayout::TwoLevel));
+    assert_eq!(run(PageTableLayout::Flat), run(PageTableLayout::Radix));
+    assert_eq!(run(PageTableLayout::Flat), run(PageTableLayout::Inverted));
+  }
+
+  #[test]
+  fn inverted_chains() {
+    // 4 frames, so 4 anchors for pages far more numerous
+    let mut table = PageTableLayout::Inverted.build(32, 4096, &[], 4).unwrap();
+    assert_eq!(table.levels(), Vec::<usize>::new());
+    assert_eq!(table.layout(), PageTableLayout::Inverted);
+    assert_eq!(table.walk(0x12345), Walk { frame: None, depth: 1 });
+
+    [0x10, 0x20, 0x30, 0x40]
+      .iter()
+      .enumerate()
+      .for_each(|(frame, &page)| table.set_frame(page, frame));
+    assert_eq!(table.chains().unwrap().iter().sum::<usize>(), 4);
+    (0..4).for_each(|frame| {
+      let page = table.page_of(frame).unwrap();
+      let walk = table.walk(page);
+      assert_eq!(walk.frame, Some(frame));
+      assert!(walk.depth >= 2);
+    });
+
+    // reusing a frame drops the page it held from its chain
+    table.set_frame(0x50, 1);
+    assert_eq!(table.get_frame(0x20), None);
+    assert_eq!(table.get_frame(0x50), Some(1));
+    table.invalidate_frame(2);
+    assert_eq!(table.get_frame(0x30), None);
+    assert_eq!(table.chains().unwrap().iter().sum::<usize>(), 3);
+    assert_eq!(table.get_frame(0x10), Some(0));
+    assert_eq!(table.get_frame(0x40), Some(3));
+  }
+
+  #[test]
+  fn dirty_bits() {
+    use crate::{pal::PALAlgorithm, simulator::Simulator};
+
+    for layout in [PageTableLayout::Flat, PageTableLayout::Radix, PageTableLayout::Inverted] {
+      let mut simulator = Simulator::new(PALAlgorithm::Fifo, 2, 4096);
+      simulator.mmu.page_table = layout.build(32, 4096, &[], 2).unwrap();
+
+      simulator.translate_str("R 1000").unwrap();
+      simulator.translate_str("X 2000").unwrap();
+      let entry = *simulator.mmu.page_table.entry_mut(1).unwrap();
+      assert!(entry.referenced && !entry.dirty);
+
+      simulator.translate_str("W 2004").unwrap();
+      assert!(simulator.mmu.page_table.entry_mut(2).unwrap().dirty);
+
+      // 1 leaves clean and 2 dirty
+      simulator.translate_str("R 3000").unwrap();
+      simulator.translate_str("R 4000").unwrap();
+      assert_eq!(simulator.mmu.page_table.entry_mut(1), None);
+      assert_eq!(simulator.mmu.dirty_evictions, 1);
+
+      let report = simulator.report("manual");
+      assert_eq!((report.evictions, report.dirty_evictions), (2, Some(1)));
+      assert_eq!(report.swap.unwrap().page_outs, 1);
+    }
+  }
+}
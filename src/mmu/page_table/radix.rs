@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use super::{unmappable, PageTable, PageTableEntry, Walk};
+
+#[derive(Debug, Clone)]
+pub enum RadixNode {
+  /// Index in `RadixPageTable::nodes` of the table each entry points to, if allocated
+  Directory(Vec<Option<usize>>),
+  Leaf(Vec<PageTableEntry>),
+}
+
+/// Hierarchical page table. The page number is split in one index per level, most significant
+/// bits first, and the tables of the lower levels are only allocated when a page below them is
+/// mapped.
+#[derive(Debug, Clone)]
+pub struct RadixPageTable {
+  /// Bits of the page number indexing every level, root first
+  pub level_bits: Vec<usize>,
+  /// Every table allocated so far, the root being the first one
+  pub nodes: Vec<RadixNode>,
+  /// Page currently mapped to each frame, so a frame can be invalidated without a walk
+  pub frame_pages: HashMap<usize, usize>,
+  /// Bytes taken by every entry
+  pub entry_size: usize,
+  pub page_size: usize,
+}
+
+impl RadixPageTable {
+  pub fn new(level_bits: Vec<usize>, entry_size: usize, page_size: usize) -> Self {
+    let root = Self::node(&level_bits, 0);
+
+    Self {
+      level_bits,
+      nodes: vec![root],
+      frame_pages: HashMap::new(),
+      entry_size,
+      page_size,
+    }
+  }
+
+  fn node(level_bits: &[usize], level: usize) -> RadixNode {
+    let entries = 1 << level_bits[level];
+
+    match level + 1 == level_bits.len() {
+      true => RadixNode::Leaf(vec![PageTableEntry::default(); entries]),
+      false => RadixNode::Directory(vec![None; entries]),
+    }
+  }
+
+  /// Index of `page` in the table of `level`, failing for pages wider than the table maps
+  fn index(&self, page: usize, level: usize) -> anyhow::Result<usize> {
+    let shift = self.level_bits[level + 1..].iter().sum::<usize>();
+    let index = page >> shift;
+
+    match level {
+      // out of range pages must not alias into the address space
+      0 if index >> self.level_bits[0] != 0 => Err(unmappable(page, self.pages())),
+      0 => Ok(index),
+      _ => Ok(index & ((1 << self.level_bits[level]) - 1)),
+    }
+  }
+
+  /// The leaf entry of `page`, allocating the missing tables on the way when `allocate` is set
+  fn leaf(&mut self, page: usize, allocate: bool) -> Option<&mut PageTableEntry> {
+    let leaf = self.level_bits.len() - 1;
+    let mut node = 0;
+
+    for level in 0..leaf {
+      let index = self.index(page, level).ok()?;
+      let RadixNode::Directory(children) = &self.nodes[node] else {
+        unreachable!("only the last level holds leaves")
+      };
+
+      node = match children[index] {
+        Some(child) => child,
+        None if allocate => {
+          let child = self.nodes.len();
+          self.nodes.push(Self::node(&self.level_bits, level + 1));
+          if let RadixNode::Directory(children) = &mut self.nodes[node] {
+            children[index] = Some(child);
+          }
+          child
+        }
+        None => return None,
+      };
+    }
+
+    let index = self.index(page, leaf).ok()?;
+    match &mut self.nodes[node] {
+      RadixNode::Leaf(entries) => entries.get_mut(index),
+      RadixNode::Directory(_) => unreachable!("the last level is always a leaf"),
+    }
+  }
+}
+
+impl PageTable for RadixPageTable {
+  fn walk(&self, page: usize) -> Walk {
+    let mut node = 0;
+    let mut depth = 0;
+
+    for level in 0..self.level_bits.len() {
+      depth += 1;
+      let Ok(index) = self.index(page, level) else {
+        break;
+      };
+
+      match &self.nodes[node] {
+        RadixNode::Leaf(entries) => {
+          let entry = entries[index];
+          return Walk {
+            frame: entry.valid.then_some(entry.page_frame_index),
+            depth,
+          };
+        }
+        RadixNode::Directory(children) => match children[index] {
+          Some(child) => node = child,
+          None => break,
+        },
+      }
+    }
+
+    Walk { frame: None, depth }
+  }
+
+  fn set_frame(&mut self, page: usize, frame: usize) {
+    self.invalidate(page);
+    self.frame_pages.insert(frame, page);
+    *self.leaf(page, true).expect("pages are checked before they are mapped") = PageTableEntry {
+      page_frame_index: frame,
+      valid: true,
+      ..Default::default()
+    };
+  }
+
+  fn invalidate(&mut self, page: usize) {
+    if let Some(entry) = self.leaf(page, false) {
+      let old = std::mem::take(entry);
+      if old.valid && self.frame_pages.get(&old.page_frame_index) == Some(&page) {
+        self.frame_pages.remove(&old.page_frame_index);
+      }
+    }
+  }
+
+  fn invalidate_frame(&mut self, frame: usize) {
+    if let Some(page) = self.frame_pages.remove(&frame) {
+      if let Some(entry) = self.leaf(page, false) {
+        entry.valid = false;
+      }
+    }
+  }
+
+  fn entry_mut(&mut self, page: usize) -> Option<&mut PageTableEntry> {
+    self.leaf(page, false).filter(|entry| entry.valid)
+  }
+
+  fn page_of(&self, frame: usize) -> Option<usize> {
+    self.frame_pages.get(&frame).copied()
+  }
+
+  fn check(&self, page: usize) -> anyhow::Result<()> {
+    self.index(page, 0).map(drop)
+  }
+
+  fn pages(&self) -> usize {
+    1 << self.level_bits.iter().sum::<usize>()
+  }
+
+  fn table_pages(&self) -> usize {
+    self
+      .nodes
+      .iter()
+      .map(|node| {
+        let entries = match node {
+          RadixNode::Directory(children) => children.len(),
+          RadixNode::Leaf(entries) => entries.len(),
+        };
+        (entries * self.entry_size).div_ceil(self.page_size)
+      })
+      .sum()
+  }
+
+  fn levels(&self) -> Vec<usize> {
+    self.level_bits.clone()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PageTable> {
+    Box::new(self.clone())
+  }
+}
@@ -0,0 +1,187 @@
+use crate::pal::{PALAlgorithm, PAL};
+
+use super::address::AccessKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TLBEntry {
+  pub page: usize,
+  pub frame: usize,
+}
+
+/// One set of the TLB. Its PAL picks the way to replace, the ways playing the part of frames.
+#[derive(Debug, Clone)]
+pub struct TLBSet {
+  pub ways: Vec<Option<TLBEntry>>,
+  pub pal: PAL,
+}
+
+/// Translation lookaside buffer, a set associative cache of page table entries in front of the
+/// page table. A page can only live in set `page % sets`.
+#[derive(Debug, Clone)]
+pub struct TLB {
+  pub sets: Vec<TLBSet>,
+  pub algorithm: PALAlgorithm,
+  pub hits: usize,
+  pub misses: usize,
+}
+
+impl TLB {
+  /// `entries` must be a multiple of `ways`, `ways == entries` makes the TLB fully associative
+  pub fn new(entries: usize, ways: usize, algorithm: PALAlgorithm) -> anyhow::Result<Self> {
+    anyhow::ensure!(
+      entries > 0 && ways > 0 && entries.is_multiple_of(ways),
+      "TLB entries ({entries}) must be a positive multiple of its ways ({ways})"
+    );
+    // sets only report hits, faults and evictions, anything more a policy needs never happens
+    let unsupported = match algorithm {
+      PALAlgorithm::Optimal => Some("needs the trace lookahead"),
+      PALAlgorithm::Script => Some("runs the policy of --policy-file, which only the PAL loads"),
+      PALAlgorithm::WorkingSet => Some("releases frames on its own"),
+      PALAlgorithm::WSClock => Some("schedules write backs"),
+      PALAlgorithm::Plugin(plugin)
+        if plugin.vtable.release.is_some()
+          || plugin.vtable.write_back.is_some()
+          || plugin.vtable.set_lookahead.is_some() =>
+      {
+        Some("needs more than hits, faults and evictions")
+      }
+      _ => None,
+    };
+    if let Some(reason) = unsupported {
+      anyhow::bail!("The {algorithm} algorithm {reason} and can't drive the TLB");
+    }
+
+    Ok(Self {
+      sets: (0..entries / ways)
+        .map(|_| TLBSet {
+          ways: vec![None; ways],
+          pal: PAL::new(algorithm, ways),
+        })
+        .collect(),
+      algorithm,
+      hits: 0,
+      misses: 0,
+    })
+  }
+
+  pub fn entries(&self) -> usize {
+    self.sets.len() * self.ways()
+  }
+
+  pub fn ways(&self) -> usize {
+    self.sets.first().map_or(0, |set| set.ways.len())
+  }
+
+  fn set(&mut self, page: usize) -> &mut TLBSet {
+    let sets = self.sets.len();
+    &mut self.sets[page % sets]
+  }
+
+  fn way(set: &TLBSet, page: usize) -> Option<usize> {
+    set
+      .ways
+      .iter()
+      .position(|entry| matches!(entry, Some(entry) if entry.page == page))
+  }
+
+  /// The frame of `page` if it is cached, counting the hit or miss
+  pub fn lookup(&mut self, page: usize) -> Option<usize> {
+    let set = self.set(page);
+    let frame = Self::way(set, page).map(|way| {
+      set.pal.on_hit(page, AccessKind::Read, way);
+      set.ways[way].unwrap().frame
+    });
+
+    match frame {
+      Some(_) => self.hits += 1,
+      None => self.misses += 1,
+    }
+    frame
+  }
+
+  /// Caches the translation of `page`, in a free way if the set has one
+  pub fn fill(&mut self, page: usize, frame: usize) {
+    let set = self.set(page);
+    set.pal.on_fault(page, AccessKind::Read);
+    let way = match set.ways.iter().position(Option::is_none) {
+      Some(way) => way,
+      None => {
+        let way = set.pal.choose_victim();
+        set.pal.on_evict(way);
+        way
+      }
+    };
+
+    set.ways[way] = Some(TLBEntry { page, frame });
+    set.pal.on_load(way);
+  }
+
+  /// Drops the translation of `page`, keeping the TLB coherent with the page table
+  pub fn invalidate(&mut self, page: usize) {
+    let set = self.set(page);
+    if let Some(way) = Self::way(set, page) {
+      set.ways[way] = None;
+      set.pal.on_evict(way);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn set_associative() {
+    // 2 sets of 2 ways, even pages go to set 0 and odd pages to set 1
+    let mut tlb = TLB::new(4, 2, PALAlgorithm::LRU).unwrap();
+
+    [(0, 10), (2, 12), (1, 11)]
+      .iter()
+      .for_each(|&(page, frame)| tlb.fill(page, frame));
+    assert_eq!(tlb.lookup(0), Some(10));
+
+    // set 0 is full, 2 is the least recently used of it
+    tlb.fill(4, 14);
+    assert_eq!(tlb.lookup(2), None);
+    assert_eq!(tlb.lookup(0), Some(10));
+    assert_eq!(tlb.lookup(4), Some(14));
+    assert_eq!(tlb.lookup(1), Some(11));
+
+    tlb.invalidate(0);
+    assert_eq!(tlb.lookup(0), None);
+    // the invalidated way is reused before evicting anyone
+    tlb.fill(6, 16);
+    assert_eq!(tlb.lookup(4), Some(14));
+    assert_eq!(tlb.lookup(6), Some(16));
+
+    assert_eq!((tlb.hits, tlb.misses), (6, 2));
+  }
+
+  #[test]
+  fn eviction_after_invalidate() {
+    let mut tlb = TLB::new(2, 2, PALAlgorithm::Fifo).unwrap();
+    tlb.fill(0, 10);
+    tlb.fill(1, 11);
+
+    // 2 takes the way of 0 as a new load, which leaves 1 the oldest
+    tlb.invalidate(0);
+    tlb.fill(2, 12);
+    tlb.fill(3, 13);
+    assert_eq!(tlb.lookup(1), None);
+    assert_eq!(tlb.lookup(2), Some(12));
+    assert_eq!(tlb.lookup(3), Some(13));
+  }
+
+  #[test]
+  fn invalid_geometry() {
+    assert!(TLB::new(6, 4, PALAlgorithm::LRU).is_err());
+    assert!(TLB::new(4, 0, PALAlgorithm::LRU).is_err());
+    assert!(TLB::new(4, 4, PALAlgorithm::Optimal).is_err());
+    assert!(TLB::new(4, 4, PALAlgorithm::Script).is_err());
+    assert!(TLB::new(4, 4, PALAlgorithm::WorkingSet).is_err());
+    assert_eq!(
+      TLB::new(4, 4, PALAlgorithm::WSClock).unwrap_err().to_string(),
+      "The ws-clock algorithm schedules write backs and can't drive the TLB"
+    );
+  }
+}
@@ -1,4 +1,5 @@
 use std::{
+  collections::HashMap,
   fs::File,
   io::{BufRead, BufReader},
   str::FromStr,
@@ -26,6 +27,10 @@ impl Trace {
     &self.addresses[index]
   }
 
+  pub fn iter(&self) -> std::slice::Iter<'_, LogicalAddress> {
+    self.addresses.iter()
+  }
+
   pub fn len(&self) -> usize {
     self.addresses.len()
   }
@@ -35,15 +40,32 @@ impl Trace {
     self.len() == 0
   }
 
+  /// Position of the next reference to the same page for every address of the trace, or
+  /// `usize::MAX` when the page is never referenced again
+  pub fn next_uses(&self, page_size: usize) -> Vec<usize> {
+    let mut next_uses = vec![usize::MAX; self.len()];
+    let mut last_seen = HashMap::new();
+
+    for (index, address) in self.addresses.iter().enumerate().rev() {
+      if let Some(next) = last_seen.insert(address.page(page_size), index) {
+        next_uses[index] = next;
+      }
+    }
+
+    next_uses
+  }
+
   pub fn from_file(path: &str) -> anyhow::Result<Self> {
     let mut trace = Self::new();
 
     let file = File::open(path)?;
     let reader = BufReader::new(file);
 
-    reader
-      .lines()
-      .for_each(|line| trace.add(LogicalAddress::from_str(&line.unwrap()).unwrap()));
+    for (number, line) in reader.lines().enumerate() {
+      let address = LogicalAddress::from_str(&line?)
+        .map_err(|error| error.context(format!("{path}:{}", number + 1)))?;
+      trace.add(address);
+    }
 
     Ok(trace)
   }
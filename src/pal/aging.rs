@@ -0,0 +1,180 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use super::{Access, PALTable};
+
+/// Top bit of `AgingEntry::history`
+const TOP: u64 = 1 << 63;
+
+/// Ordered as victims: a pending referenced bit is newer than the whole register, ties go to the
+/// oldest load as for the other count based policies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AgingEntry {
+  /// Referenced since the last tick
+  pub referenced: bool,
+  /// Latest tick the frame was referenced in, `None` once the register is all zeros
+  pub newest: Option<u64>,
+  /// Register bits from `newest` on, in the most significant bit. Every register is aligned on
+  /// the current tick, so comparing `(newest, history)` compares registers without shifting them
+  pub history: u64,
+  pub loaded_at: usize,
+}
+
+/// Aging: every `interval` references, each frame's register is shifted right with its
+/// referenced bit entering at the top. The frame with the lowest register goes, which
+/// approximates LRU at the resolution of a tick and over the last `bits` ticks.
+///
+/// Registers are not shifted on every tick, a tick only updates the frames referenced since the
+/// previous one and the frames losing their oldest bit, so a reference costs O(log frames).
+#[derive(Debug, Clone)]
+pub struct AgingPALTable {
+  pub entries: HashMap<usize, AgingEntry>,
+  /// Frames in victim order
+  pub queue: BTreeSet<(AgingEntry, usize)>,
+  /// Frames referenced since the last tick
+  pub pending: Vec<usize>,
+  /// Frames every one of the last `bits` ticks gave a bit to, with their load order to tell them
+  /// from a later load in the same frame, oldest tick first
+  pub ticks: VecDeque<Vec<(usize, usize)>>,
+  pub bits: u32,
+  pub interval: usize,
+  /// References seen, a tick happens whenever they reach a multiple of `interval`
+  pub references: usize,
+  /// Ticks so far
+  pub now: u64,
+  pub loads: usize,
+}
+
+impl AgingPALTable {
+  pub fn new(capacity: usize, bits: u32, interval: usize) -> Self {
+    Self {
+      entries: HashMap::with_capacity(capacity),
+      queue: BTreeSet::new(),
+      pending: Vec::new(),
+      ticks: VecDeque::with_capacity(bits as usize),
+      bits,
+      interval,
+      references: 0,
+      now: 0,
+      loads: 0,
+    }
+  }
+
+  /// Counts a reference, shifting the registers every `interval` of them
+  fn reference(&mut self) {
+    self.references += 1;
+    if self.references.is_multiple_of(self.interval) {
+      self.tick();
+    }
+  }
+
+  fn update(&mut self, frame: usize, update: impl FnOnce(&mut AgingEntry)) {
+    if let Some(entry) = self.entries.get_mut(&frame) {
+      self.queue.remove(&(*entry, frame));
+      update(entry);
+      self.queue.insert((*entry, frame));
+    }
+  }
+
+  fn tick(&mut self) {
+    self.now += 1;
+    let now = self.now;
+
+    // the oldest bit leaves the registers
+    if self.ticks.len() == self.bits as usize {
+      let oldest = now - self.bits as u64;
+      for (frame, loaded_at) in self.ticks.pop_front().unwrap_or_default() {
+        if self.entries.get(&frame).is_some_and(|entry| entry.loaded_at == loaded_at) {
+          self.update(frame, |entry| match entry.newest {
+            Some(newest) if newest > oldest => entry.history &= !(TOP >> (newest - oldest)),
+            _ => *entry = AgingEntry { newest: None, history: 0, ..*entry },
+          });
+        }
+      }
+    }
+
+    // and the referenced bits enter at the top
+    let mut referenced = Vec::new();
+    for frame in std::mem::take(&mut self.pending) {
+      if !self.entries.get(&frame).is_some_and(|entry| entry.referenced) {
+        continue;
+      }
+      self.update(frame, |entry| {
+        entry.history = match entry.newest {
+          Some(newest) => (entry.history >> (now - newest)) | TOP,
+          None => TOP,
+        };
+        entry.newest = Some(now);
+        entry.referenced = false;
+        referenced.push((frame, entry.loaded_at));
+      });
+    }
+    self.ticks.push_back(referenced);
+  }
+
+  /// Register of `entry` as of the latest tick
+  fn register(&self, entry: &AgingEntry) -> u64 {
+    match entry.newest {
+      Some(newest) => (entry.history >> (64 - self.bits)) >> (self.now - newest),
+      None => 0,
+    }
+  }
+}
+
+impl PALTable for AgingPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if self.entries.get(&frame).is_some_and(|entry| !entry.referenced) {
+      self.update(frame, |entry| entry.referenced = true);
+      self.pending.push(frame);
+    }
+    self.reference();
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let (_, frame) = self.queue.first().unwrap();
+    *frame
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    if let Some(entry) = self.entries.remove(&frame) {
+      self.queue.remove(&(entry, frame));
+    }
+  }
+
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    // loading the page is its first reference
+    let entry = AgingEntry {
+      referenced: true,
+      newest: None,
+      history: 0,
+      loaded_at: self.loads,
+    };
+    self.loads += 1;
+    self.entries.insert(frame, entry);
+    self.queue.insert((entry, frame));
+    self.pending.push(frame);
+    self.reference();
+  }
+
+  fn resident(&self) -> usize {
+    self.entries.len()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("Aging PAL Table {{");
+    let mut entries = self.entries.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(frame, _)| **frame);
+    for (frame, entry) in entries {
+      println!(
+        "   [{frame}]: {:0width$b} {}",
+        self.register(entry),
+        entry.referenced as u8,
+        width = self.bits as usize
+      );
+    }
+    println!("}}");
+  }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use super::{list::FrameList, Access, PALTable};
+
+/// Adaptive Replacement Cache (Megiddo and Modha). T1 holds the pages seen once recently and T2
+/// the ones seen at least twice, B1 and B2 remember the pages recently evicted from each. A hit
+/// in B1 grows the target size `p` of T1, a hit in B2 shrinks it, so that a scan only churns T1
+/// while the frequently used pages stay in T2.
+///
+/// The lists are keyed by page, as the ghosts outlive the frames that held them.
+#[derive(Debug, Clone)]
+pub struct ARCPALTable {
+  /// Resident pages seen once and their frames, least recent first
+  pub t1: FrameList<usize>,
+  /// Resident pages seen more than once and their frames, least recent first
+  pub t2: FrameList<usize>,
+  /// Pages evicted from T1, least recent first
+  pub b1: FrameList<()>,
+  /// Pages evicted from T2, least recent first
+  pub b2: FrameList<()>,
+  /// Target size of T1
+  pub p: usize,
+  /// Page held by every frame
+  pub pages: HashMap<usize, usize>,
+  /// Page of the fault being served
+  pub current: Option<usize>,
+  /// Whether the victim chosen for the fault being served leaves no ghost
+  pub forget: bool,
+  pub capacity: usize,
+}
+
+impl ARCPALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      t1: FrameList::with_capacity(capacity),
+      t2: FrameList::with_capacity(capacity),
+      b1: FrameList::with_capacity(capacity),
+      b2: FrameList::with_capacity(capacity),
+      p: 0,
+      pages: HashMap::with_capacity(capacity),
+      current: None,
+      forget: false,
+      capacity,
+    }
+  }
+
+  /// Frame of the page to evict from T1 or T2, depending on the target `p`
+  fn replace(&self, page: Option<usize>) -> usize {
+    let in_b2 = page.is_some_and(|page| self.b2.contains(page));
+    let from_t1 = !self.t1.is_empty()
+      && (self.t1.len() > self.p || (in_b2 && self.t1.len() == self.p) || self.t2.is_empty());
+
+    let list = match from_t1 {
+      true => &self.t1,
+      false => &self.t2,
+    };
+    *list.get(list.front().unwrap()).unwrap()
+  }
+}
+
+impl PALTable for ARCPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    let Some(&page) = self.pages.get(&frame) else {
+      return;
+    };
+
+    if let Some(frame) = self.t1.remove(page) {
+      self.t2.push_back(page, frame);
+    } else {
+      self.t2.move_to_back(page);
+    }
+  }
+
+  fn on_fault(&mut self, access: &Access) {
+    let page = access.page;
+    self.current = Some(page);
+
+    // a ghost hit adapts `p` towards the list that would have kept the page
+    if self.b1.contains(page) {
+      let delta = (self.b2.len() / self.b1.len()).max(1);
+      self.p = (self.p + delta).min(self.capacity);
+    } else if self.b2.contains(page) {
+      let delta = (self.b1.len() / self.b2.len()).max(1);
+      self.p = self.p.saturating_sub(delta);
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let c = self.capacity;
+    let page = self.current;
+
+    if page.is_some_and(|page| self.b1.contains(page) || self.b2.contains(page)) {
+      return self.replace(page);
+    }
+
+    if self.t1.len() + self.b1.len() >= c {
+      if self.t1.len() >= c {
+        // B1 is empty, the least recent page of T1 goes without leaving a ghost
+        self.forget = true;
+        return *self.t1.get(self.t1.front().unwrap()).unwrap();
+      }
+      self.b1.pop_front();
+    } else if self.t1.len() + self.t2.len() + self.b1.len() + self.b2.len() >= 2 * c {
+      self.b2.pop_front();
+    }
+    self.replace(page)
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    let forget = std::mem::take(&mut self.forget);
+    let Some(page) = self.pages.remove(&frame) else {
+      return;
+    };
+
+    if self.t1.remove(page).is_some() {
+      if !forget {
+        self.b1.push_back(page, ());
+      }
+    } else if self.t2.remove(page).is_some() {
+      self.b2.push_back(page, ());
+    }
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    let page = access.page;
+    self.current = None;
+
+    // a ghost hit means the page was used before, it goes to T2
+    let seen = self.b1.remove(page).is_some() | self.b2.remove(page).is_some();
+    match seen {
+      true => self.t2.push_back(page, frame),
+      false => self.t1.push_back(page, frame),
+    }
+    self.pages.insert(frame, page);
+  }
+
+  fn resident(&self) -> usize {
+    self.pages.len()
+  }
+
+  fn parameters(&self) -> Vec<(&'static str, usize)> {
+    vec![
+      ("p", self.p),
+      ("t1", self.t1.len()),
+      ("t2", self.t2.len()),
+      ("b1", self.b1.len()),
+      ("b2", self.b2.len()),
+    ]
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    let pages = |list: &FrameList<_>| list.iter().map(|(page, _)| page).collect::<Vec<_>>();
+
+    println!("ARC PAL Table (p = {}) {{", self.p);
+    println!("   T1: {:?}", pages(&self.t1));
+    println!("   T2: {:?}", pages(&self.t2));
+    println!("   B1: {:?}", self.b1.iter().map(|(page, _)| page).collect::<Vec<_>>());
+    println!("   B2: {:?}", self.b2.iter().map(|(page, _)| page).collect::<Vec<_>>());
+    println!("}}");
+  }
+}
@@ -0,0 +1,70 @@
+use super::{list::FrameRing, Access, PALTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockEntry {
+  pub referenced: bool,
+}
+
+/// CLOCK: the frames sit on a circle and the hand keeps its position across evictions, clearing
+/// referenced bits until it finds a frame without one. The new frame replaces the victim in
+/// place, and the hand moves past it.
+#[derive(Debug, Clone)]
+pub struct ClockPALTable {
+  pub ring: FrameRing<ClockEntry>,
+}
+
+impl ClockPALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      ring: FrameRing::with_capacity(capacity),
+    }
+  }
+}
+
+impl PALTable for ClockPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if let Some(entry) = self.ring.get_mut(frame) {
+      entry.referenced = true;
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    loop {
+      if let Some((frame, entry)) = self.ring.slot_mut(self.ring.hand) {
+        if !entry.referenced {
+          return frame;
+        }
+        entry.referenced = false;
+      }
+      self.ring.advance();
+    }
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    self.ring.remove(frame);
+  }
+
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    self.ring.insert(frame, ClockEntry { referenced: false });
+  }
+
+  fn resident(&self) -> usize {
+    self.ring.len()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("Clock PAL Table {{");
+    for slot in 0..self.ring.slots() {
+      let hand = if slot == self.ring.hand { " <-" } else { "" };
+      match self.ring.slot(slot) {
+        Some((frame, entry)) => println!("   [{frame}]: {}{hand}", entry.referenced),
+        None => println!("   [ ]{hand}"),
+      }
+    }
+    println!("}}");
+  }
+}
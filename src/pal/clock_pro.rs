@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use super::{list::FrameList, Access, PALTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockProStatus {
+  Hot,
+  Cold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockProEntry {
+  pub status: ClockProStatus,
+  pub referenced: bool,
+  /// A cold page referenced again during its test period becomes hot
+  pub test: bool,
+  /// Frame of the page, `None` for a non-resident cold page still in its test period
+  pub frame: Option<usize>,
+}
+
+/// CLOCK-Pro (Jiang, Chen and Zhang), the clock approximation of LIRS. Hot pages are the ones
+/// reused within a short distance, cold pages get a test period after their load and become hot
+/// if referenced again before it ends, even after their eviction. Three hands share the clock:
+/// HAND_cold evicts cold pages, HAND_hot demotes hot pages past the hot share of the frames and
+/// ends the test periods it passes, as HAND_test does for the pages the clock remembers. The
+/// cold share grows on every reference to a non-resident page in its test period and shrinks on
+/// every test period ending without one.
+#[derive(Debug, Clone)]
+pub struct ClockProPALTable {
+  /// The clock, by page. HAND_hot always sits at the front, so pages join right behind it
+  pub clock: FrameList<ClockProEntry>,
+  pub hand_cold: Option<usize>,
+  pub hand_test: Option<usize>,
+  /// Page held by every frame
+  pub pages: HashMap<usize, usize>,
+  pub capacity: usize,
+  /// Frames for cold pages, adapted between 1 and all of them
+  pub cold_target: usize,
+  pub hot: usize,
+  /// Resident cold pages
+  pub cold: usize,
+  /// Non-resident cold pages in their test period, at most as many as frames
+  pub nonresident: usize,
+}
+
+impl ClockProPALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      clock: FrameList::with_capacity(2 * capacity),
+      hand_cold: None,
+      hand_test: None,
+      pages: HashMap::with_capacity(capacity),
+      capacity,
+      cold_target: 1,
+      hot: 0,
+      cold: 0,
+      nonresident: 0,
+    }
+  }
+
+  /// Page after `page` on the clock
+  fn step(&self, page: usize) -> Option<usize> {
+    self.clock.next(page).or(self.clock.front())
+  }
+
+  /// Moves the hands off `page`, before it leaves its place on the clock
+  fn unhook(&mut self, page: usize) {
+    let next = self.step(page).filter(|&next| next != page);
+    for hand in [&mut self.hand_cold, &mut self.hand_test] {
+      if *hand == Some(page) {
+        *hand = next;
+      }
+    }
+  }
+
+  fn remove(&mut self, page: usize) {
+    self.unhook(page);
+    self.clock.remove(page);
+  }
+
+  fn end_test(&mut self, page: usize) {
+    let entry = self.clock.get_mut(page).unwrap();
+    entry.test = false;
+    if entry.frame.is_none() {
+      self.remove(page);
+      self.nonresident -= 1;
+    }
+    self.cold_target = (self.cold_target - 1).max(1);
+  }
+
+  /// Moves HAND_cold one page, unless it points at an unreferenced cold page. That page's frame
+  /// is returned, and the hand stays on it until its eviction
+  fn run_hand_cold(&mut self) -> Option<usize> {
+    let page = self.hand_cold.or(self.clock.front())?;
+    let entry = *self.clock.get(page).unwrap();
+    if let (ClockProStatus::Cold, Some(frame), false) = (entry.status, entry.frame, entry.referenced)
+    {
+      return Some(frame);
+    }
+    self.hand_cold = self.step(page);
+
+    if let (ClockProStatus::Cold, Some(_)) = (entry.status, entry.frame) {
+      let entry = self.clock.get_mut(page).unwrap();
+      match entry.test {
+        true => {
+          *entry = ClockProEntry {
+            status: ClockProStatus::Hot,
+            referenced: false,
+            test: false,
+            ..*entry
+          };
+          self.cold -= 1;
+          self.hot += 1;
+        }
+        false => {
+          entry.referenced = false;
+          entry.test = true;
+          self.unhook(page);
+          self.clock.move_to_back(page);
+        }
+      }
+    }
+
+    self.run_hand_hot_to_target();
+    None
+  }
+
+  fn run_hand_hot_to_target(&mut self) {
+    while self.hot > self.capacity - self.cold_target {
+      self.run_hand_hot();
+    }
+  }
+
+  /// Moves HAND_hot one page, by sending the front page to the back of the clock
+  fn run_hand_hot(&mut self) {
+    let Some(page) = self.clock.front() else {
+      return;
+    };
+
+    let entry = self.clock.get_mut(page).unwrap();
+    match entry.status {
+      ClockProStatus::Hot if entry.referenced => entry.referenced = false,
+      ClockProStatus::Hot => {
+        entry.status = ClockProStatus::Cold;
+        self.hot -= 1;
+        self.cold += 1;
+      }
+      ClockProStatus::Cold if entry.test => self.end_test(page),
+      ClockProStatus::Cold => {}
+    }
+
+    self.clock.move_to_back(page);
+  }
+
+  fn run_hand_test(&mut self) {
+    let Some(page) = self.hand_test.or(self.clock.front()) else {
+      return;
+    };
+    self.hand_test = self.step(page);
+
+    let entry = self.clock.get(page).unwrap();
+    if entry.status == ClockProStatus::Cold && entry.test {
+      self.end_test(page);
+    }
+  }
+}
+
+impl PALTable for ClockProPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if let Some(&page) = self.pages.get(&frame) {
+      if let Some(entry) = self.clock.get_mut(page) {
+        entry.referenced = true;
+      }
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    loop {
+      if let Some(frame) = self.run_hand_cold() {
+        return frame;
+      }
+    }
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    let Some(page) = self.pages.remove(&frame) else {
+      return;
+    };
+
+    let entry = *self.clock.get(page).unwrap();
+    match entry.status {
+      // only when the MMU forces a hot page out
+      ClockProStatus::Hot => {
+        self.remove(page);
+        self.hot -= 1;
+      }
+      ClockProStatus::Cold => {
+        if self.hand_cold == Some(page) {
+          self.hand_cold = self.step(page);
+        }
+        self.cold -= 1;
+
+        if entry.test {
+          self.clock.get_mut(page).unwrap().frame = None;
+          self.nonresident += 1;
+          while self.nonresident > self.capacity {
+            self.run_hand_test();
+          }
+        } else {
+          self.remove(page);
+        }
+      }
+    }
+
+    self.run_hand_hot_to_target();
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    let page = access.page;
+    let status = match self.clock.get(page) {
+      // back during its test period: the page is hot, and cold pages deserve more frames
+      Some(_) => {
+        self.remove(page);
+        self.nonresident -= 1;
+        self.cold_target = (self.cold_target + 1).min(self.capacity);
+        self.hot += 1;
+        ClockProStatus::Hot
+      }
+      None => {
+        self.cold += 1;
+        ClockProStatus::Cold
+      }
+    };
+    self.clock.push_back(
+      page,
+      ClockProEntry {
+        status,
+        referenced: false,
+        test: status == ClockProStatus::Cold,
+        frame: Some(frame),
+      },
+    );
+    self.pages.insert(frame, page);
+  }
+
+  fn resident(&self) -> usize {
+    self.pages.len()
+  }
+
+  fn parameters(&self) -> Vec<(&'static str, usize)> {
+    vec![
+      ("hot", self.hot),
+      ("cold", self.cold),
+      ("test", self.nonresident),
+      ("cold_target", self.cold_target),
+    ]
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("CLOCK-Pro PAL Table {{");
+    for (page, entry) in self.clock.iter() {
+      let mut hands = String::new();
+      if self.hand_cold == Some(page) {
+        hands.push_str(" <-cold");
+      }
+      if self.hand_test == Some(page) {
+        hands.push_str(" <-test");
+      }
+      let frame = entry.frame.map_or("-".to_string(), |frame| frame.to_string());
+      println!(
+        "   [{page}]: {:?} {frame} ({}, {}){hands}",
+        entry.status, entry.referenced as u8, entry.test as u8
+      );
+    }
+    println!("}}");
+  }
+}
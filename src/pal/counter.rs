@@ -1,65 +1,144 @@
-use super::PALTable;
+use std::collections::{BTreeSet, HashMap};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+use super::{Access, PALTable};
+
+/// Count based policies share their tie-break: among frames with the same count, the one loaded
+/// first goes. Load order is unique, so the victim never depends on frame numbers or hashing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CounterPALTableEntry {
-  pub frame: usize,
+  /// References since the frame was loaded, the load included
   pub times_accessed: usize,
+  /// Load order of the frame, breaks ties between frames accessed the same number of times
+  pub loaded_at: usize,
 }
 
+/// Frequency based replacement: LFU (the `Counter` algorithm), LFU whose counts are halved
+/// every `decay_interval` references so that old popularity fades, or MFU.
+///
+/// LFU spares the frame loaded by the latest fault: its single reference is the lowest count
+/// there is, so it would otherwise go at the next fault before it had a chance to be used again.
 #[derive(Debug, Clone)]
 pub struct CounterPALTable {
-  pub entries: Vec<CounterPALTableEntry>,
+  pub entries: HashMap<usize, CounterPALTableEntry>,
+  /// Frames ordered by access count, least accessed (and then oldest) first
+  pub queue: BTreeSet<(CounterPALTableEntry, usize)>,
+  pub loads: usize,
+  /// Evict the most accessed frame instead of the least accessed one
+  pub most_frequent: bool,
+  pub decay_interval: Option<usize>,
+  pub references: usize,
 }
 
-impl PALTable for CounterPALTable {
-  fn find_frame_to_deallocate(&mut self) -> usize {
-    let mut min = (0, usize::MAX);
-    for CounterPALTableEntry { frame, times_accessed } in self.entries.iter() {
-      if times_accessed < &min.1 {
-        min = (*frame, *times_accessed);
-      }
+impl CounterPALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      entries: HashMap::with_capacity(capacity),
+      queue: BTreeSet::new(),
+      loads: 0,
+      most_frequent: false,
+      decay_interval: None,
+      references: 0,
     }
+  }
 
-    let (frame, _) = min;
-    self.entries.retain(|x| x.frame != frame);
-    frame
+  pub fn with_decay(capacity: usize, decay_interval: usize) -> Self {
+    Self {
+      decay_interval: Some(decay_interval),
+      ..Self::new(capacity)
+    }
   }
 
-  fn update_access(&mut self, index: usize) {
-    self.entries[index].times_accessed += 1;
+  pub fn most_frequent(capacity: usize) -> Self {
+    Self {
+      most_frequent: true,
+      ..Self::new(capacity)
+    }
   }
 
-  fn insert(&mut self, frame: usize) -> Option<usize> {
-    match self.entries.iter().position(|x| x.frame == frame) {
-      Some(index) => {
-        self.update_access(index);
-        None
+  /// Counts a reference, decaying the counts every `decay_interval` of them
+  fn reference(&mut self) {
+    self.references += 1;
+    if let Some(interval) = self.decay_interval {
+      if self.references.is_multiple_of(interval) {
+        self.decay();
       }
-      None => {
-        let frame_to_deallocate = if self.entries.len() >= self.entries.capacity() {
-          Some(self.find_frame_to_deallocate())
-        } else {
-          None
-        };
+    }
+  }
+
+  fn decay(&mut self) {
+    self.entries.values_mut().for_each(|entry| entry.times_accessed /= 2);
+    self.queue = self
+      .entries
+      .iter()
+      .map(|(&frame, &entry)| (entry, frame))
+      .collect();
+  }
+}
 
-        self.entries.push(CounterPALTableEntry {
-          frame,
-          times_accessed: 0,
-        });
+impl PALTable for CounterPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    self.reference();
+    if let Some(entry) = self.entries.get_mut(&frame) {
+      self.queue.remove(&(*entry, frame));
+      entry.times_accessed += 1;
+      self.queue.insert((*entry, frame));
+    }
+  }
 
-        frame_to_deallocate
+  fn on_fault(&mut self, _access: &Access) {
+    self.reference();
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let (_, frame) = match self.most_frequent {
+      false => *self
+        .queue
+        .iter()
+        .find(|(entry, _)| entry.loaded_at + 1 != self.loads)
+        .or(self.queue.first())
+        .unwrap(),
+      true => {
+        // the oldest of the most accessed frames
+        let (most, _) = *self.queue.last().unwrap();
+        let oldest = CounterPALTableEntry {
+          times_accessed: most.times_accessed,
+          loaded_at: 0,
+        };
+        *self.queue.range((oldest, 0)..).next().unwrap()
       }
+    };
+    frame
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    if let Some(entry) = self.entries.remove(&frame) {
+      self.queue.remove(&(entry, frame));
     }
   }
 
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    let entry = CounterPALTableEntry {
+      times_accessed: 1,
+      loaded_at: self.loads,
+    };
+    self.loads += 1;
+    self.entries.insert(frame, entry);
+    self.queue.insert((entry, frame));
+  }
+
+  fn resident(&self) -> usize {
+    self.entries.len()
+  }
+
   fn clone_dyn(&self) -> Box<dyn PALTable> {
     Box::new(self.clone())
   }
 
   fn print(&self) {
     println!("Counter PAL Table {{");
-    for CounterPALTableEntry { frame, times_accessed: last_access } in self.entries.iter() {
-      println!("   [{frame}]: {last_access}");
+    // in eviction order for LFU, reversed for MFU
+    for (CounterPALTableEntry { times_accessed, .. }, frame) in self.queue.iter() {
+      println!("   [{frame}]: {times_accessed}");
     }
     println!("}}");
   }
@@ -0,0 +1,92 @@
+use super::{list::FrameRing, Access, PALTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnhancedSecondChanceEntry {
+  pub referenced: bool,
+  pub modified: bool,
+}
+
+/// Enhanced second chance (NRU) clock. Frames are ranked by their (referenced, modified) bits and
+/// the hand takes the first frame of the lowest class: (0, 0), then (0, 1) clearing the
+/// referenced bits it passes, then both again, so at most four sweeps. Clean frames go first,
+/// saving the write back.
+#[derive(Debug, Clone)]
+pub struct EnhancedSecondChancePALTable {
+  pub ring: FrameRing<EnhancedSecondChanceEntry>,
+}
+
+impl EnhancedSecondChancePALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      ring: FrameRing::with_capacity(capacity),
+    }
+  }
+}
+
+impl PALTable for EnhancedSecondChancePALTable {
+  fn on_hit(&mut self, access: &Access, frame: usize) {
+    if let Some(entry) = self.ring.get_mut(frame) {
+      entry.referenced = true;
+      entry.modified |= access.kind.is_write();
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    for sweep in 0..4 {
+      // odd sweeps look for modified frames and take away the second chances
+      let modified = sweep % 2 == 1;
+
+      for step in 0..self.ring.slots() {
+        let slot = self.ring.ahead(step);
+        let Some((frame, entry)) = self.ring.slot_mut(slot) else {
+          continue;
+        };
+
+        if !entry.referenced && entry.modified == modified {
+          self.ring.hand = slot;
+          return frame;
+        }
+        if modified {
+          entry.referenced = false;
+        }
+      }
+    }
+
+    unreachable!("every frame is (0, 0) or (0, 1) by the fourth sweep")
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    self.ring.remove(frame);
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    let entry = EnhancedSecondChanceEntry {
+      referenced: false,
+      modified: access.kind.is_write(),
+    };
+    self.ring.insert(frame, entry);
+  }
+
+  fn resident(&self) -> usize {
+    self.ring.len()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("EnhancedSecondChance PAL Table {{");
+    for slot in 0..self.ring.slots() {
+      let hand = if slot == self.ring.hand { " <-" } else { "" };
+      match self.ring.slot(slot) {
+        Some((frame, entry)) => println!(
+          "   [{frame}]: ({}, {}){hand}",
+          entry.referenced as u8, entry.modified as u8
+        ),
+        None => println!("   [ ]{hand}"),
+      }
+    }
+    println!("}}");
+  }
+}
@@ -0,0 +1,42 @@
+use super::{list::FrameList, Access, PALTable};
+
+#[derive(Debug, Clone)]
+pub struct FifoPALTable {
+  /// Frames in load order, oldest first
+  pub entries: FrameList<()>,
+}
+
+impl PALTable for FifoPALTable {
+  fn on_hit(&mut self, _access: &Access, _frame: usize) {
+    // FIFO ignores accesses to frames already loaded
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    // the oldest frame is always at the front of the queue
+    self.entries.front().unwrap()
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    self.entries.remove(frame);
+  }
+
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    self.entries.push_back(frame, ());
+  }
+
+  fn resident(&self) -> usize {
+    self.entries.len()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("FIFO PAL Table {{");
+    for (position, (frame, _)) in self.entries.iter().enumerate() {
+      println!("   [{frame}]: {position}");
+    }
+    println!("}}");
+  }
+}
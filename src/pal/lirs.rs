@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use super::{list::FrameList, Access, PALTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LIRSEntry {
+  /// Low inter-reference recency page, kept resident
+  pub lir: bool,
+  /// Frame of the page, `None` for the non-resident HIR pages the stack remembers
+  pub frame: Option<usize>,
+}
+
+/// Low Inter-reference Recency Set (Jiang and Zhang). Pages whose last two references were close
+/// (LIR) stay resident, and a small share of the frames holds the other (HIR) pages, evicted in
+/// FIFO order. The recency stack S remembers HIR pages after their eviction, so that one
+/// referenced again sooner than the oldest LIR page becomes LIR in its place.
+#[derive(Debug, Clone)]
+pub struct LIRSPALTable {
+  /// Recency stack S, bottom (least recent) first. Its bottom is always a LIR page
+  pub stack: FrameList<()>,
+  /// Resident HIR pages, the next victim first
+  pub queue: FrameList<()>,
+  /// Non-resident HIR pages in the stack, oldest first, at most as many as frames
+  pub ghosts: FrameList<()>,
+  pub entries: HashMap<usize, LIRSEntry>,
+  /// Page held by every frame
+  pub pages: HashMap<usize, usize>,
+  pub capacity: usize,
+  /// Frames for LIR pages, all but 1% of them (at least one frame goes to HIR pages)
+  pub lir_capacity: usize,
+  pub lirs: usize,
+}
+
+impl LIRSPALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      stack: FrameList::with_capacity(capacity),
+      queue: FrameList::with_capacity(capacity),
+      ghosts: FrameList::with_capacity(capacity),
+      entries: HashMap::with_capacity(capacity),
+      pages: HashMap::with_capacity(capacity),
+      capacity,
+      lir_capacity: capacity - (capacity / 100).max(1).min(capacity),
+      lirs: 0,
+    }
+  }
+
+  /// Drops the HIR pages at the bottom of the stack, forgetting the non-resident ones
+  fn prune(&mut self) {
+    while let Some(bottom) = self.stack.front() {
+      let entry = self.entries[&bottom];
+      if entry.lir {
+        break;
+      }
+
+      self.stack.remove(bottom);
+      if entry.frame.is_none() {
+        self.entries.remove(&bottom);
+        self.ghosts.remove(bottom);
+      }
+    }
+  }
+
+  /// Turns `page` into a LIR page on top of the stack, demoting the bottom LIR page if there
+  /// are too many
+  fn promote(&mut self, page: usize) {
+    self.entries.get_mut(&page).unwrap().lir = true;
+    self.lirs += 1;
+    self.queue.remove(page);
+    self.ghosts.remove(page);
+    self.stack.remove(page);
+    self.stack.push_back(page, ());
+
+    if self.lirs > self.lir_capacity {
+      if let Some((bottom, _)) = self.stack.pop_front() {
+        self.entries.get_mut(&bottom).unwrap().lir = false;
+        self.lirs -= 1;
+        self.queue.push_back(bottom, ());
+      }
+    }
+    self.prune();
+  }
+
+  fn access(&mut self, page: usize) {
+    let entry = self.entries[&page];
+
+    match (entry.lir, self.stack.contains(page)) {
+      (true, _) => {
+        self.stack.move_to_back(page);
+        self.prune();
+      }
+      // resident HIR page reused within the stack: its recency beats the oldest LIR page
+      (false, true) => self.promote(page),
+      (false, false) => {
+        self.stack.push_back(page, ());
+        self.queue.move_to_back(page);
+      }
+    }
+  }
+
+  fn load(&mut self, page: usize, frame: usize) {
+    self.pages.insert(frame, page);
+
+    match self.entries.get_mut(&page) {
+      Some(entry) => {
+        entry.frame = Some(frame);
+        self.promote(page);
+      }
+      None => {
+        let lir = self.lirs < self.lir_capacity;
+        self.entries.insert(page, LIRSEntry { lir, frame: Some(frame) });
+        self.stack.push_back(page, ());
+        match lir {
+          true => self.lirs += 1,
+          false => self.queue.push_back(page, ()),
+        }
+      }
+    }
+  }
+}
+
+impl PALTable for LIRSPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if let Some(&page) = self.pages.get(&frame) {
+      self.access(page);
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let page = self.queue.front().unwrap();
+    self.entries[&page].frame.unwrap()
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    let Some(page) = self.pages.remove(&frame) else {
+      return;
+    };
+
+    // only when the MMU forces a LIR page out
+    if self.entries[&page].lir {
+      self.entries.remove(&page);
+      self.stack.remove(page);
+      self.lirs -= 1;
+      self.prune();
+      return;
+    }
+
+    self.queue.remove(page);
+    self.entries.get_mut(&page).unwrap().frame = None;
+    match self.stack.contains(page) {
+      true => {
+        self.ghosts.push_back(page, ());
+        if self.ghosts.len() > self.capacity {
+          let (oldest, _) = self.ghosts.pop_front().unwrap();
+          self.stack.remove(oldest);
+          self.entries.remove(&oldest);
+        }
+      }
+      false => {
+        self.entries.remove(&page);
+      }
+    }
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    self.load(access.page, frame);
+  }
+
+  fn resident(&self) -> usize {
+    self.pages.len()
+  }
+
+  fn parameters(&self) -> Vec<(&'static str, usize)> {
+    vec![
+      ("lir", self.lirs),
+      ("hir", self.queue.len()),
+      ("ghosts", self.ghosts.len()),
+      ("stack", self.stack.len()),
+    ]
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("LIRS PAL Table {{");
+    for (page, _) in self.stack.iter() {
+      let entry = self.entries[&page];
+      let status = match (entry.lir, entry.frame) {
+        (true, _) => "LIR".to_string(),
+        (false, Some(_)) => "resident HIR".to_string(),
+        (false, None) => "non-resident HIR".to_string(),
+      };
+      println!("   [{page}]: {status}");
+    }
+    println!("   Q: {:?}", self.queue.iter().map(|(page, _)| page).collect::<Vec<_>>());
+    println!("}}");
+  }
+}
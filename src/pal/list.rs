@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+  frame: usize,
+  value: T,
+  prev: Option<usize>,
+  next: Option<usize>,
+}
+
+/// Doubly linked list of frames stored in a slab, with a hash index from frame to node, so that
+/// lookups, removals and moves to the back are O(1). The order is the one the frames were pushed
+/// (or moved) in, front being the oldest.
+#[derive(Debug, Clone)]
+pub struct FrameList<T> {
+  nodes: Vec<Node<T>>,
+  index: HashMap<usize, usize>,
+  free: Vec<usize>,
+  head: Option<usize>,
+  tail: Option<usize>,
+}
+
+impl<T> FrameList<T> {
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      nodes: Vec::with_capacity(capacity),
+      index: HashMap::with_capacity(capacity),
+      free: Vec::new(),
+      head: None,
+      tail: None,
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.index.len()
+  }
+
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn contains(&self, frame: usize) -> bool {
+    self.index.contains_key(&frame)
+  }
+
+  pub fn get(&self, frame: usize) -> Option<&T> {
+    self.index.get(&frame).map(|&slot| &self.nodes[slot].value)
+  }
+
+  pub fn get_mut(&mut self, frame: usize) -> Option<&mut T> {
+    self
+      .index
+      .get(&frame)
+      .map(|&slot| &mut self.nodes[slot].value)
+  }
+
+  pub fn front(&self) -> Option<usize> {
+    self.head.map(|slot| self.nodes[slot].frame)
+  }
+
+  /// The frame right after `frame`, if any
+  pub fn next(&self, frame: usize) -> Option<usize> {
+    let slot = *self.index.get(&frame)?;
+    self.nodes[slot].next.map(|next| self.nodes[next].frame)
+  }
+
+  pub fn push_back(&mut self, frame: usize, value: T) {
+    let node = Node {
+      frame,
+      value,
+      prev: self.tail,
+      next: None,
+    };
+
+    let slot = match self.free.pop() {
+      Some(slot) => {
+        self.nodes[slot] = node;
+        slot
+      }
+      None => {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+      }
+    };
+
+    match self.tail {
+      Some(tail) => self.nodes[tail].next = Some(slot),
+      None => self.head = Some(slot),
+    }
+    self.tail = Some(slot);
+    self.index.insert(frame, slot);
+  }
+
+  pub fn pop_front(&mut self) -> Option<(usize, T)>
+  where
+    T: Clone,
+  {
+    let frame = self.front()?;
+    self.remove(frame).map(|value| (frame, value))
+  }
+
+  pub fn remove(&mut self, frame: usize) -> Option<T>
+  where
+    T: Clone,
+  {
+    let slot = self.index.remove(&frame)?;
+    self.unlink(slot);
+    self.free.push(slot);
+
+    Some(self.nodes[slot].value.clone())
+  }
+
+  pub fn move_to_back(&mut self, frame: usize) {
+    let Some(&slot) = self.index.get(&frame) else {
+      return;
+    };
+
+    if self.tail == Some(slot) {
+      return;
+    }
+
+    self.unlink(slot);
+    self.nodes[slot].prev = self.tail;
+    self.nodes[slot].next = None;
+    match self.tail {
+      Some(tail) => self.nodes[tail].next = Some(slot),
+      None => self.head = Some(slot),
+    }
+    self.tail = Some(slot);
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+    std::iter::successors(self.head, move |&slot| self.nodes[slot].next).map(move |slot| {
+      let node = &self.nodes[slot];
+      (node.frame, &node.value)
+    })
+  }
+
+  fn unlink(&mut self, slot: usize) {
+    let Node { prev, next, .. } = self.nodes[slot];
+
+    match prev {
+      Some(prev) => self.nodes[prev].next = next,
+      None => self.head = next,
+    }
+    match next {
+      Some(next) => self.nodes[next].prev = prev,
+      None => self.tail = prev,
+    }
+  }
+}
+
+/// Circle of frames for the clock policies, with its hand. An evicted frame leaves its slot empty
+/// and the frame loaded next takes it back, so the others keep their place on the circle.
+#[derive(Debug, Clone)]
+pub struct FrameRing<T> {
+  slots: Vec<Option<(usize, T)>>,
+  /// Slot of every loaded frame
+  positions: HashMap<usize, usize>,
+  pub hand: usize,
+}
+
+impl<T> FrameRing<T> {
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      slots: Vec::with_capacity(capacity),
+      positions: HashMap::with_capacity(capacity),
+      hand: 0,
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.positions.len()
+  }
+
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Slots of the circle, the empty ones included
+  pub fn slots(&self) -> usize {
+    self.slots.len()
+  }
+
+  /// Frame in `slot` and its value, unless the slot is empty
+  pub fn slot(&self, slot: usize) -> Option<(usize, &T)> {
+    self.slots[slot].as_ref().map(|(frame, value)| (*frame, value))
+  }
+
+  pub fn slot_mut(&mut self, slot: usize) -> Option<(usize, &mut T)> {
+    self.slots[slot].as_mut().map(|(frame, value)| (*frame, value))
+  }
+
+  pub fn get_mut(&mut self, frame: usize) -> Option<&mut T> {
+    let slot = *self.positions.get(&frame)?;
+    self.slot_mut(slot).map(|(_, value)| value)
+  }
+
+  /// The slot `step` slots past the hand
+  pub fn ahead(&self, step: usize) -> usize {
+    (self.hand + step) % self.slots.len()
+  }
+
+  pub fn advance(&mut self) {
+    self.hand = self.ahead(1);
+  }
+
+  /// Places `frame` in the slot under the hand, if a victim left it empty, and moves the hand past
+  /// it. Otherwise the frame takes any slot a frame left, or a new one while the circle grows.
+  pub fn insert(&mut self, frame: usize, value: T) {
+    let slot = match self.slots.get(self.hand) {
+      Some(None) => {
+        let slot = self.hand;
+        self.advance();
+        slot
+      }
+      _ => match self.len() < self.slots.len() {
+        true => self.slots.iter().position(Option::is_none).unwrap(),
+        false => {
+          self.slots.push(None);
+          self.slots.len() - 1
+        }
+      },
+    };
+    self.slots[slot] = Some((frame, value));
+    self.positions.insert(frame, slot);
+  }
+
+  pub fn remove(&mut self, frame: usize) -> Option<T> {
+    let slot = self.positions.remove(&frame)?;
+    self.slots[slot].take().map(|(_, value)| value)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn frames<T>(list: &FrameList<T>) -> Vec<usize> {
+    list.iter().map(|(frame, _)| frame).collect()
+  }
+
+  #[test]
+  fn frame_list() {
+    let mut list = FrameList::with_capacity(4);
+    assert!(list.is_empty());
+    assert_eq!(list.front(), None);
+    [3, 1, 4].iter().for_each(|&frame| list.push_back(frame, frame * 10));
+    assert_eq!(frames(&list), vec![3, 1, 4]);
+    assert_eq!((list.len(), list.front(), list.next(1), list.next(4)), (3, Some(3), Some(4), None));
+
+    // moves keep the links of both neighbours
+    list.move_to_back(1);
+    assert_eq!(frames(&list), vec![3, 4, 1]);
+    list.move_to_back(1);
+    list.move_to_back(9);
+    assert_eq!(frames(&list), vec![3, 4, 1]);
+    list.move_to_back(3);
+    assert_eq!(frames(&list), vec![4, 1, 3]);
+
+    *list.get_mut(1).unwrap() += 1;
+    assert_eq!((list.get(1), list.get(2), list.contains(4)), (Some(&11), None, true));
+
+    // removed nodes are reused, from the middle or either end
+    assert_eq!(list.remove(1), Some(11));
+    assert_eq!(list.remove(1), None);
+    assert_eq!(list.pop_front(), Some((4, 40)));
+    list.push_back(5, 50);
+    list.push_back(6, 60);
+    assert_eq!(frames(&list), vec![3, 5, 6]);
+    assert_eq!(list.remove(6), Some(60));
+    assert_eq!(list.remove(3), Some(30));
+    assert_eq!((frames(&list), list.front(), list.next(5)), (vec![5], Some(5), None));
+    assert_eq!(list.pop_front(), Some((5, 50)));
+    assert_eq!((list.pop_front(), list.len()), (None, 0));
+    list.push_back(7, 70);
+    assert_eq!(frames(&list), vec![7]);
+  }
+
+  #[test]
+  fn frame_ring() {
+    let mut ring = FrameRing::with_capacity(3);
+    [7, 8, 9].iter().for_each(|&frame| ring.insert(frame, frame * 10));
+    // the circle grows while filling, the hand stays put
+    assert_eq!((ring.len(), ring.slots(), ring.hand), (3, 3, 0));
+    assert_eq!(ring.slot(1), Some((8, &80)));
+
+    // a victim under the hand is replaced in place, the hand moving past it
+    ring.hand = 1;
+    assert_eq!(ring.remove(8), Some(80));
+    assert_eq!(ring.slot(1), None);
+    ring.insert(4, 40);
+    assert_eq!((ring.slot(1), ring.hand), (Some((4, &40)), 2));
+
+    // a frame gone elsewhere leaves a hole the next load fills
+    ring.remove(7);
+    ring.insert(5, 50);
+    assert_eq!((ring.slot(0), ring.slots(), ring.hand), (Some((5, &50)), 3, 2));
+    *ring.get_mut(5).unwrap() += 1;
+    assert_eq!(ring.slot(ring.ahead(1)), Some((5, &51)));
+    assert_eq!(ring.remove(6), None);
+  }
+}
@@ -1,58 +1,34 @@
-use chrono::{DateTime, Utc};
-
-use super::PALTable;
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct LRUPALTableEntry {
-  pub frame: usize,
-  pub last_access: DateTime<Utc>,
-}
+use super::{list::FrameList, Access, PALTable};
 
+/// Least recently used. Frames are kept in recency order, so the victim is always at the front.
 #[derive(Debug, Clone)]
 pub struct LRUPALTable {
-  pub entries: Vec<LRUPALTableEntry>,
+  /// Frames and the tick of their last access, least recently used first
+  pub entries: FrameList<u64>,
 }
 
 impl PALTable for LRUPALTable {
-  fn find_frame_to_deallocate(&mut self) -> usize {
-    let mut min = (0, i64::MAX);
-    for LRUPALTableEntry { frame, last_access } in self.entries.iter() {
-      let last_access = last_access.timestamp_millis();
-      if last_access < min.1 {
-        min = (*frame, last_access);
-      }
+  fn on_hit(&mut self, access: &Access, frame: usize) {
+    if let Some(last_access) = self.entries.get_mut(frame) {
+      *last_access = access.time;
+      self.entries.move_to_back(frame);
     }
-
-    let (frame, _) = min;
-    self.entries.retain(|x| x.frame != frame);
-    frame
   }
 
-  fn update_access(&mut self, index: usize) {
-    self.entries[index].last_access = Utc::now();
+  fn choose_victim(&mut self) -> usize {
+    self.entries.front().unwrap()
   }
 
-  fn insert(&mut self, frame: usize) -> Option<usize> {
-    match self.entries.iter().position(|x| x.frame == frame) {
-      Some(index) => {
-        self.update_access(index);
-        None
-      }
-      None => {
-        let frame_to_deallocate = if self.entries.len() >= self.entries.capacity() {
-          Some(self.find_frame_to_deallocate())
-        } else {
-          None
-        };
+  fn on_evict(&mut self, frame: usize) {
+    self.entries.remove(frame);
+  }
 
-        self.entries.push(LRUPALTableEntry {
-          frame,
-          last_access: Utc::now(),
-        });
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    self.entries.push_back(frame, access.time);
+  }
 
-        frame_to_deallocate
-      }
-    }
+  fn resident(&self) -> usize {
+    self.entries.len()
   }
 
   fn clone_dyn(&self) -> Box<dyn PALTable> {
@@ -61,12 +37,8 @@ impl PALTable for LRUPALTable {
 
   fn print(&self) {
     println!("LRU PAL Table {{");
-    for LRUPALTableEntry { frame, last_access } in self.entries.iter() {
-      println!(
-        "   [{frame}]: {}",
-        last_access.format("%Y-%m-%d %H:%M:%S%.3f"),
-        frame = frame
-      );
+    for (frame, last_access) in self.entries.iter() {
+      println!("   [{frame}]: {last_access}");
     }
     println!("}}");
   }
@@ -1,20 +1,103 @@
-use clap::ValueEnum;
-use core::fmt::Debug;
-use singleton_manager::sm;
-use std::collections::VecDeque;
-use std::{str::FromStr, sync::Mutex};
+use clap::{
+  builder::{EnumValueParser, PossibleValue, TypedValueParser},
+  error::ErrorKind,
+  ValueEnum,
+};
+use core::fmt::{Debug, Display};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
 
+use crate::{cli::pal::PALOptions, clock::Clock, mmu::address::AccessKind};
+
+use self::aging::AgingPALTable;
+use self::arc::ARCPALTable;
+use self::clock::ClockPALTable;
+use self::clock_pro::ClockProPALTable;
 use self::counter::CounterPALTable;
+use self::enhanced_second_chance::EnhancedSecondChancePALTable;
+use self::fifo::FifoPALTable;
+use self::lirs::LIRSPALTable;
+use self::list::FrameList;
+use self::optimal::OptimalPALTable;
+use self::plugin::{Plugin, PluginPALTable};
+use self::random::RandomPALTable;
+use self::s3_fifo::S3FifoPALTable;
+use self::script::ScriptPALTable;
 use self::second_chance::SecondChancePALTable;
+use self::sieve::SievePALTable;
+use self::two_queue::TwoQueuePALTable;
+use self::working_set::WorkingSetPALTable;
+use self::wsclock::WSClockPALTable;
 
+pub mod aging;
+pub mod arc;
+pub mod clock;
+pub mod clock_pro;
 pub mod counter;
+pub mod enhanced_second_chance;
+pub mod fifo;
+pub mod lirs;
+pub mod list;
 pub mod lru;
+pub mod optimal;
+pub mod plugin;
+pub mod random;
+pub mod s3_fifo;
+pub mod script;
 pub mod second_chance;
+pub mod sieve;
+pub mod two_queue;
+pub mod working_set;
+pub mod wsclock;
+
+/// A reference as the policies see it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Access {
+  pub page: usize,
+  pub kind: AccessKind,
+  /// Tick of the simulation clock, one per reference
+  pub time: u64,
+}
 
-pub trait PALTable {
-  fn find_frame_to_deallocate(&mut self) -> usize;
-  fn update_access(&mut self, frame: usize);
-  fn insert(&mut self, frame: usize) -> Option<usize>;
+/// A replacement policy, driven by the events of every reference. A hit is a single `on_hit`.
+/// A fault is `on_fault`, then `choose_victim` and `on_evict` if memory is full, and `on_load`
+/// once the page has its frame. Frames may also leave through `on_evict` alone, when the policy
+/// releases them or a cache drops them.
+pub trait PALTable: Send {
+  /// `access.page`, resident in `frame`, was referenced
+  fn on_hit(&mut self, access: &Access, frame: usize);
+  /// `access.page` is not resident. Comes before the victim is chosen, for policies that adapt
+  /// to the page coming in
+  fn on_fault(&mut self, _access: &Access) {}
+  /// Frame to free for the fault being served. The policy may move hands or clear bits on the
+  /// way, but the frame stays in the table until `on_evict`
+  fn choose_victim(&mut self) -> usize;
+  /// The page in `frame` left memory
+  fn on_evict(&mut self, frame: usize);
+  /// The page of the fault being served was loaded into `frame`
+  fn on_load(&mut self, access: &Access, frame: usize);
+  /// Frames holding a page
+  fn resident(&self) -> usize;
+  /// Receives the next-use position of every reference of the trace, for policies that look ahead
+  fn set_lookahead(&mut self, _next_uses: Vec<usize>) {}
+  /// A frame the policy wants freed without a fault asking for it. It leaves through
+  /// `on_evict`, and the question is asked again until the answer is `None`
+  fn release(&mut self) -> Option<usize> {
+    None
+  }
+  /// A frame whose page the policy scheduled the write back of while choosing a victim. The MMU
+  /// writes it to swap and marks it clean, asking again until the answer is `None`
+  fn write_back(&mut self) -> Option<usize> {
+    None
+  }
+  /// An error the policy ran into, the run carrying on with a fallback decision
+  fn error(&self) -> Option<&str> {
+    None
+  }
+  /// Adaptive state of the policy, logged by the verbose output
+  fn parameters(&self) -> Vec<(&'static str, usize)> {
+    vec![]
+  }
   fn clone_dyn(&self) -> Box<dyn PALTable>;
   fn print(&self);
 }
@@ -30,6 +113,44 @@ pub enum PALAlgorithm {
   LRU,
   Counter,
   SecondChance,
+  Fifo,
+  Optimal,
+  EnhancedSecondChance,
+  Clock,
+  Aging,
+  LfuDecay,
+  Mfu,
+  WorkingSet,
+  WSClock,
+  ARC,
+  LIRS,
+  #[value(name = "2q")]
+  TwoQueue,
+  ClockPro,
+  Sieve,
+  S3Fifo,
+  Random,
+  Script,
+  /// Policy of a dynamic library, given as `plugin:<path>`
+  #[value(skip)]
+  Plugin(&'static Plugin),
+}
+
+impl PALAlgorithm {
+  /// Whether the runs depend on `--seed`
+  pub fn is_seeded(&self) -> bool {
+    matches!(self, PALAlgorithm::Random)
+  }
+}
+
+impl Display for PALAlgorithm {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    // same names the CLI accepts
+    match self {
+      PALAlgorithm::Plugin(plugin) => write!(f, "plugin:{}", plugin.path),
+      _ => write!(f, "{}", self.to_possible_value().unwrap().get_name()),
+    }
+  }
 }
 
 impl FromStr for PALAlgorithm {
@@ -40,75 +161,254 @@ impl FromStr for PALAlgorithm {
       "lru" => Ok(PALAlgorithm::LRU),
       "counter" => Ok(PALAlgorithm::Counter),
       "second_chance" => Ok(PALAlgorithm::SecondChance),
-      _ => Err(format!("Unknown algorithm: {}", s)),
+      "fifo" => Ok(PALAlgorithm::Fifo),
+      "optimal" => Ok(PALAlgorithm::Optimal),
+      "enhanced_second_chance" => Ok(PALAlgorithm::EnhancedSecondChance),
+      "clock" => Ok(PALAlgorithm::Clock),
+      "aging" => Ok(PALAlgorithm::Aging),
+      "lfu_decay" => Ok(PALAlgorithm::LfuDecay),
+      "mfu" => Ok(PALAlgorithm::Mfu),
+      "working_set" => Ok(PALAlgorithm::WorkingSet),
+      "ws_clock" => Ok(PALAlgorithm::WSClock),
+      "arc" => Ok(PALAlgorithm::ARC),
+      "lirs" => Ok(PALAlgorithm::LIRS),
+      "2q" => Ok(PALAlgorithm::TwoQueue),
+      "clock_pro" => Ok(PALAlgorithm::ClockPro),
+      "sieve" => Ok(PALAlgorithm::Sieve),
+      "s3_fifo" => Ok(PALAlgorithm::S3Fifo),
+      "random" => Ok(PALAlgorithm::Random),
+      "script" => Ok(PALAlgorithm::Script),
+      _ => match s.strip_prefix("plugin:") {
+        Some(path) => Plugin::load(path)
+          .map(PALAlgorithm::Plugin)
+          .map_err(|error| format!("{error:#}")),
+        None => Err(format!("Unknown algorithm: {}", s)),
+      },
+    }
+  }
+}
+
+/// Parses the CLI names of the algorithms, and `plugin:<path>`
+#[derive(Debug, Clone, Copy)]
+pub struct PALAlgorithmParser;
+
+impl TypedValueParser for PALAlgorithmParser {
+  type Value = PALAlgorithm;
+
+  fn parse_ref(
+    &self,
+    cmd: &clap::Command,
+    arg: Option<&clap::Arg>,
+    value: &std::ffi::OsStr,
+  ) -> Result<Self::Value, clap::Error> {
+    match value.to_str().and_then(|value| value.strip_prefix("plugin:")) {
+      Some(path) => Plugin::load(path).map(PALAlgorithm::Plugin).map_err(|error| {
+        let arg = arg.map_or("...".to_string(), ToString::to_string);
+        let value = value.to_string_lossy();
+        let message = format!("invalid value '{value}' for '{arg}': {error:#}\n");
+        clap::Error::raw(ErrorKind::ValueValidation, message).with_cmd(cmd)
+      }),
+      None => EnumValueParser::<PALAlgorithm>::new().parse_ref(cmd, arg, value),
     }
   }
+
+  fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+    Some(Box::new(
+      PALAlgorithm::value_variants()
+        .iter()
+        .filter_map(ValueEnum::to_possible_value),
+    ))
+  }
 }
 
 #[derive(Debug)]
 pub struct PAL {
   pub table: Box<dyn PALTable>,
-  pub guard: Mutex<()>,
+  /// Simulation clock, advanced once per reference
+  pub clock: Clock,
+  /// Frames holding a page, as the events told
+  pub frames: HashSet<usize>,
+  /// Frames available, only needed to drive the policy without an MMU
+  pub capacity: usize,
+  /// The fault being served, from `on_fault` to `on_load`
+  pub fault: Option<Access>,
 }
 
 impl Clone for PAL {
   fn clone(&self) -> Self {
     Self {
       table: self.table.clone_dyn(),
-      guard: Mutex::new(()),
+      clock: self.clock.clone(),
+      frames: self.frames.clone(),
+      capacity: self.capacity,
+      fault: self.fault,
     }
   }
 }
 
 impl PAL {
   pub fn new(algorithm: PALAlgorithm, frame_count: usize) -> Self {
-    match algorithm {
-      PALAlgorithm::Counter => Self {
-        table: Box::new(CounterPALTable {
-          entries: Vec::with_capacity(frame_count),
-        }),
-        guard: Mutex::new(()),
-      },
-      PALAlgorithm::LRU => Self {
-        table: Box::new(lru::LRUPALTable {
-          entries: Vec::with_capacity(frame_count),
-        }),
-        guard: Mutex::new(()),
-      },
-      PALAlgorithm::SecondChance => Self {
-        table: Box::new(SecondChancePALTable {
-          entries: VecDeque::with_capacity(frame_count),
-        }),
-        guard: Mutex::new(()),
-      },
+    Self::with_options(algorithm, frame_count, &PALOptions::default())
+  }
+
+  pub fn with_options(algorithm: PALAlgorithm, frame_count: usize, options: &PALOptions) -> Self {
+    Self::with_clock(algorithm, frame_count, options, Clock::default())
+  }
+
+  pub fn with_clock(
+    algorithm: PALAlgorithm,
+    frame_count: usize,
+    options: &PALOptions,
+    clock: Clock,
+  ) -> Self {
+    let table: Box<dyn PALTable> = match algorithm {
+      PALAlgorithm::Counter => Box::new(CounterPALTable::new(frame_count)),
+      PALAlgorithm::LfuDecay => Box::new(CounterPALTable::with_decay(
+        frame_count,
+        options.decay_interval,
+      )),
+      PALAlgorithm::Mfu => Box::new(CounterPALTable::most_frequent(frame_count)),
+      PALAlgorithm::WorkingSet => Box::new(WorkingSetPALTable::new(frame_count, options.tau)),
+      PALAlgorithm::WSClock => Box::new(WSClockPALTable::new(frame_count, options.tau)),
+      PALAlgorithm::ARC => Box::new(ARCPALTable::new(frame_count)),
+      PALAlgorithm::LIRS => Box::new(LIRSPALTable::new(frame_count)),
+      PALAlgorithm::TwoQueue => Box::new(TwoQueuePALTable::new(frame_count)),
+      PALAlgorithm::ClockPro => Box::new(ClockProPALTable::new(frame_count)),
+      PALAlgorithm::Sieve => Box::new(SievePALTable::new(frame_count)),
+      PALAlgorithm::S3Fifo => Box::new(S3FifoPALTable::new(frame_count)),
+      PALAlgorithm::Random => Box::new(RandomPALTable::new(frame_count, options.seed)),
+      PALAlgorithm::Plugin(plugin) => Box::new(PluginPALTable::new(plugin, frame_count)),
+      PALAlgorithm::Script => Box::new(ScriptPALTable::new(
+        frame_count,
+        options
+          .policy_file
+          .clone()
+          .expect("the script algorithm is only run with a --policy-file"),
+      )),
+      PALAlgorithm::LRU => Box::new(lru::LRUPALTable {
+        entries: FrameList::with_capacity(frame_count),
+      }),
+      PALAlgorithm::SecondChance => Box::new(SecondChancePALTable {
+        entries: FrameList::with_capacity(frame_count),
+      }),
+      PALAlgorithm::Fifo => Box::new(FifoPALTable {
+        entries: FrameList::with_capacity(frame_count),
+      }),
+      PALAlgorithm::Optimal => Box::new(OptimalPALTable {
+        entries: HashMap::with_capacity(frame_count),
+        queue: BTreeSet::new(),
+        loads: 0,
+        next_uses: Vec::new(),
+        position: 0,
+      }),
+      PALAlgorithm::EnhancedSecondChance => {
+        Box::new(EnhancedSecondChancePALTable::new(frame_count))
+      }
+      PALAlgorithm::Clock => Box::new(ClockPALTable::new(frame_count)),
+      PALAlgorithm::Aging => Box::new(AgingPALTable::new(
+        frame_count,
+        options.aging_bits,
+        options.aging_interval,
+      )),
+    };
+
+    Self {
+      table,
+      clock,
+      frames: HashSet::with_capacity(frame_count),
+      capacity: frame_count,
+      fault: None,
+    }
+  }
+
+  fn access(&self, page: usize, kind: AccessKind) -> Access {
+    Access {
+      page,
+      kind,
+      time: self.clock.tick(),
     }
   }
 
-  pub fn find_frame_to_deallocate(&mut self) -> usize {
-    let _guard = self.guard.lock().unwrap();
-    self.table.find_frame_to_deallocate()
+  pub fn on_hit(&mut self, page: usize, kind: AccessKind, frame: usize) {
+    debug_assert!(self.frames.contains(&frame));
+    let access = self.access(page, kind);
+    self.table.on_hit(&access, frame)
   }
 
-  pub fn update_access(&mut self, frame: usize) {
-    let _guard = self.guard.lock().unwrap();
-    self.table.update_access(frame)
+  pub fn on_fault(&mut self, page: usize, kind: AccessKind) {
+    let access = self.access(page, kind);
+    self.fault = Some(access);
+    self.table.on_fault(&access)
   }
 
+  pub fn choose_victim(&mut self) -> usize {
+    self.table.choose_victim()
+  }
+
+  pub fn on_evict(&mut self, frame: usize) {
+    self.frames.remove(&frame);
+    self.table.on_evict(frame);
+    debug_assert_eq!(self.table.resident(), self.frames.len());
+  }
+
+  /// Ends the fault `on_fault` started, its page being loaded into `frame`
+  pub fn on_load(&mut self, frame: usize) {
+    let access = self.fault.take().expect("on_load without a fault being served");
+    self.frames.insert(frame);
+    self.table.on_load(&access, frame);
+    debug_assert_eq!(self.table.resident(), self.frames.len());
+  }
+
+  pub fn resident(&self) -> usize {
+    self.table.resident()
+  }
+
+  /// Serves a read of `frame` without an MMU, the frame standing for its own page: a hit if it
+  /// is loaded, otherwise a fault evicting the victim once every frame is taken. Returns the
+  /// victim
   pub fn insert(&mut self, frame: usize) -> Option<usize> {
-    let _guard = self.guard.lock().unwrap();
-    self.table.insert(frame)
+    self.reference(frame, AccessKind::Read)
   }
 
-  pub fn print(&self) {
-    self.table.print()
+  /// Same as `insert`, for any kind of access
+  pub fn reference(&mut self, frame: usize, kind: AccessKind) -> Option<usize> {
+    if self.frames.contains(&frame) {
+      self.on_hit(frame, kind, frame);
+      return None;
+    }
+
+    self.on_fault(frame, kind);
+    let victim = (self.frames.len() >= self.capacity).then(|| {
+      let victim = self.choose_victim();
+      self.on_evict(victim);
+      victim
+    });
+    self.on_load(frame);
+    victim
+  }
+
+  pub fn set_lookahead(&mut self, next_uses: Vec<usize>) {
+    self.table.set_lookahead(next_uses)
+  }
+
+  pub fn release(&mut self) -> Option<usize> {
+    self.table.release()
+  }
+
+  pub fn write_back(&mut self) -> Option<usize> {
+    self.table.write_back()
+  }
+
+  pub fn error(&self) -> Option<&str> {
+    self.table.error()
   }
 
-  pub fn get() -> &'static mut Self {
-    sm().get::<Self>("PAL").unwrap()
+  pub fn parameters(&self) -> Vec<(&'static str, usize)> {
+    self.table.parameters()
   }
 
-  pub fn create(algorithm: PALAlgorithm, frame_count: usize) {
-    sm().set("PAL", Self::new(algorithm, frame_count)).unwrap();
+  pub fn print(&self) {
+    self.table.print()
   }
 }
 
@@ -116,13 +416,33 @@ impl PAL {
 mod tests {
   use super::*;
 
+  /// Inserts the pages in turn, each one evicting the frame it is paired with
+  fn assert_faults(pal: &mut PAL, steps: &[(usize, Option<usize>)]) {
+    for &(page, victim) in steps {
+      assert_eq!(pal.insert(page), victim, "inserting {page}");
+    }
+  }
+
   #[test]
   fn test_lru() {
     let mut pal = PAL::new(PALAlgorithm::LRU, 4);
 
-    [0, 0, 1, 2, 1, 3, 3, 5, 5].iter().for_each(|&x| {
-      println!("[{x}] - {:?}", pal.insert(x));
-    });
+    assert_faults(
+      &mut pal,
+      &[
+        (0, None),
+        (0, None),
+        (1, None),
+        (2, None),
+        (1, None),
+        (3, None),
+        (3, None),
+        (5, Some(0)),
+        (5, None),
+        (0, Some(2)),
+        (6, Some(1)),
+      ],
+    );
   }
 
   #[test]
@@ -130,84 +450,711 @@ mod tests {
     let mut pal = PAL::new(PALAlgorithm::SecondChance, 4);
 
     // all elements are setted as accessed
-    [
-      (0, None),
-      (0, None),
-      (1, None),
-      (2, None),
-      (2, None),
-      (1, None),
-      (3, None),
-      (3, None),
-      (5, Some(0)),
-      (5, None),
-    ]
-    .iter()
-    .for_each(|(x, expected)| {
-      let insertion_result = pal.insert(*x);
-      println!("[{x}] - {:?}", insertion_result);
-      assert_eq!(insertion_result, *expected);
-    });
+    assert_faults(
+      &mut pal,
+      &[
+        (0, None),
+        (0, None),
+        (1, None),
+        (2, None),
+        (2, None),
+        (1, None),
+        (3, None),
+        (3, None),
+        (5, Some(0)),
+        (5, None),
+      ],
+    );
 
     // 2 and 3 not accessed, but 2 is removed because of the queue
-    println!("======================");
     pal = PAL::new(PALAlgorithm::SecondChance, 4);
-    [
-      (0, None),
-      (0, None),
-      (1, None),
-      (2, None),
-      (1, None),
-      (3, None),
-      (5, Some(2)),
-      (5, None),
-    ]
-    .iter()
-    .for_each(|(x, expected)| {
-      let insertion_result = pal.insert(*x);
-      println!("[{x}] - {:?}", insertion_result);
-      assert_eq!(insertion_result, *expected);
-    });
+    assert_faults(
+      &mut pal,
+      &[
+        (0, None),
+        (0, None),
+        (1, None),
+        (2, None),
+        (1, None),
+        (3, None),
+        (5, Some(2)),
+        (5, None),
+      ],
+    );
 
     // only 2 is not accessed
-    println!("======================");
     let mut pal = PAL::new(PALAlgorithm::SecondChance, 4);
-    [
-      (0, None),
-      (0, None),
-      (1, None),
-      (2, None),
-      (1, None),
-      (3, None),
-      (3, None),
-      (5, Some(2)),
-      (5, None),
-    ]
-    .iter()
-    .for_each(|(x, expected)| {
-      let insertion_result = pal.insert(*x);
-      println!("[{x}] - {:?}", insertion_result);
-      assert_eq!(insertion_result, *expected);
-    });
+    assert_faults(
+      &mut pal,
+      &[
+        (0, None),
+        (0, None),
+        (1, None),
+        (2, None),
+        (1, None),
+        (3, None),
+        (3, None),
+        (5, Some(2)),
+        (5, None),
+      ],
+    );
 
     // 1 and 2 not accessed, but 1 is removed because of the queue
-    println!("======================");
     let mut pal = PAL::new(PALAlgorithm::SecondChance, 4);
+    assert_faults(
+      &mut pal,
+      &[
+        (0, None),
+        (0, None),
+        (1, None),
+        (2, None),
+        (3, None),
+        (3, None),
+        (5, Some(1)),
+        (5, None),
+      ],
+    );
+  }
+
+  #[test]
+  fn test_fifo() {
+    let mut pal = PAL::new(PALAlgorithm::Fifo, 4);
+
+    // the first frame loaded is the first one evicted, regardless of accesses
+    assert_faults(
+      &mut pal,
+      &[
+        (0, None),
+        (0, None),
+        (1, None),
+        (2, None),
+        (2, None),
+        (1, None),
+        (3, None),
+        (3, None),
+        (5, Some(0)),
+        (5, None),
+      ],
+    );
+
+    // re-accessing 1 does not save it, unlike second chance
+    let mut pal = PAL::new(PALAlgorithm::Fifo, 4);
+    assert_faults(
+      &mut pal,
+      &[
+        (0, None),
+        (1, None),
+        (2, None),
+        (3, None),
+        (1, None),
+        (4, Some(0)),
+        (1, None),
+        (5, Some(1)),
+        (6, Some(2)),
+        (1, Some(3)),
+      ],
+    );
+  }
+
+  #[test]
+  fn test_optimal() {
+    let mut pal = PAL::new(PALAlgorithm::Optimal, 3);
+
+    // textbook reference string, OPT takes 9 faults with 3 frames
+    let references = [7, 0, 1, 2, 0, 3, 0, 4, 2, 3, 0, 3, 2, 1, 2, 0, 1, 7, 0, 1];
+    let mut next_uses = vec![usize::MAX; references.len()];
+    for (i, page) in references.iter().enumerate() {
+      if let Some(j) = references[i + 1..].iter().position(|x| x == page) {
+        next_uses[i] = i + 1 + j;
+      }
+    }
+    pal.set_lookahead(next_uses);
+
+    let steps = references
+      .iter()
+      .copied()
+      .zip([
+        None,
+        None,
+        None,
+        Some(7),
+        None,
+        Some(1),
+        None,
+        Some(0),
+        None,
+        None,
+        Some(4),
+        None,
+        None,
+        Some(3),
+        None,
+        None,
+        None,
+        Some(2),
+        None,
+        None,
+      ])
+      .collect::<Vec<_>>();
+    assert_faults(&mut pal, &steps);
+  }
+
+  #[test]
+  fn test_enhanced_second_chance() {
+    let mut pal = PAL::new(PALAlgorithm::EnhancedSecondChance, 4);
     [
-      (0, None),
-      (0, None),
-      (1, None),
-      (2, None),
-      (3, None),
-      (3, None),
-      (5, Some(1)),
-      (5, None),
+      (0, AccessKind::Write),
+      (1, AccessKind::Read),
+      (2, AccessKind::Write),
+      (3, AccessKind::Read),
     ]
     .iter()
-    .for_each(|(x, expected)| {
-      let insertion_result = pal.insert(*x);
-      println!("[{x}] - {:?}", insertion_result);
-      assert_eq!(insertion_result, *expected);
+    .for_each(|(frame, kind)| assert_eq!(pal.reference(*frame, *kind), None));
+
+    // classes: 0 is (1, 1), 1 is (1, 0), 2 is (0, 1) and 3 is (0, 0)
+    pal.insert(0);
+    pal.insert(1);
+
+    // the first sweep finds the only (0, 0) frame, and 4 takes its slot
+    assert_eq!(pal.insert(4), Some(3));
+    // 4 is now the only (0, 0) frame
+    assert_eq!(pal.insert(5), Some(4));
+
+    // no (0, 0) frame, the second sweep clears the referenced bits of 0 and 1 on its way to the
+    // modified 2, even though 1 became (0, 0) behind the hand
+    pal.insert(5);
+    assert_eq!(pal.insert(6), Some(2));
+    // the hand moved past 6, 5 kept its second chance as the sweep stopped before it
+    assert_eq!(pal.insert(7), Some(1));
+    assert_eq!(pal.insert(8), Some(6));
+
+    // every frame (1, 1): four sweeps, and the frame under the hand goes
+    let mut pal = PAL::new(PALAlgorithm::EnhancedSecondChance, 3);
+    [0, 1, 2].iter().for_each(|frame| {
+      pal.insert(*frame);
+      pal.reference(*frame, AccessKind::Write);
     });
+    assert_eq!(pal.insert(3), Some(0));
+    // the others are (0, 1) now, so the clean newcomers go before them
+    assert_eq!(pal.insert(4), Some(3));
+    assert_eq!(pal.insert(5), Some(4));
+
+    // Tanenbaum, Modern Operating Systems, chapter 3 problems: pages loaded in the order 3, 0, 2,
+    // 1 with (R, M) bits (1, 1), (1, 0), (0, 0) and (0, 1). NRU and second chance replace 2, FIFO 3
+    let snapshot = |algorithm| {
+      let mut pal = PAL::new(algorithm, 4);
+      [
+        (3, AccessKind::Write),
+        (0, AccessKind::Read),
+        (2, AccessKind::Read),
+        (1, AccessKind::Write),
+        (3, AccessKind::Read),
+        (0, AccessKind::Read),
+      ]
+      .iter()
+      .for_each(|(page, kind)| assert_eq!(pal.reference(*page, *kind), None));
+      pal
+    };
+    assert_eq!(snapshot(PALAlgorithm::Fifo).insert(4), Some(3));
+    assert_eq!(snapshot(PALAlgorithm::SecondChance).insert(4), Some(2));
+
+    let mut pal = snapshot(PALAlgorithm::EnhancedSecondChance);
+    assert_eq!(pal.reference(4, AccessKind::Write), Some(2));
+    // 4 takes 2's slot as (0, 1), the hand moves on to 1, the first (0, 1) frame it meets
+    assert_eq!(pal.reference(5, AccessKind::Write), Some(1));
+    // no (0, 0) frame: the (0, 1) sweep clears the referenced bits of 3 and 0 before reaching 4
+    assert_eq!(pal.reference(6, AccessKind::Read), Some(4));
+    // 0 became (0, 0) in that sweep, 5 and 3 are (0, 1)
+    assert_eq!(pal.reference(7, AccessKind::Read), Some(0));
+  }
+
+  #[test]
+  fn test_clock() {
+    let run = |algorithm| {
+      let mut pal = PAL::new(algorithm, 3);
+      [0, 1, 2, 0, 3, 4, 5, 6]
+        .iter()
+        .filter_map(|frame| pal.insert(*frame))
+        .collect::<Vec<_>>()
+    };
+
+    // both give 0 its second chance and take 1. The queue then restarts from its front, where 0
+    // still is, while the hand goes on to 2 and only comes back to 0 after a full turn
+    assert_eq!(run(PALAlgorithm::SecondChance), vec![1, 0, 2, 3]);
+    assert_eq!(run(PALAlgorithm::Clock), vec![1, 2, 0, 3]);
+
+    // frames are replaced in place, the hand stops right after the new one
+    let mut pal = PAL::new(PALAlgorithm::Clock, 4);
+    [0, 1, 2, 3, 1, 2].iter().for_each(|frame| {
+      pal.insert(*frame);
+    });
+    assert_eq!(pal.insert(4), Some(0));
+    // 1 and 2 lose their bits on the way to 3
+    assert_eq!(pal.insert(5), Some(3));
+    assert_eq!(pal.insert(6), Some(4));
+    assert_eq!(pal.insert(7), Some(1));
+  }
+
+  #[test]
+  fn test_aging() {
+    let options = PALOptions {
+      aging_bits: 4,
+      aging_interval: 2,
+      ..PALOptions::default()
+    };
+    let mut pal = PAL::with_options(PALAlgorithm::Aging, 3, &options);
+
+    // ticks after every second reference: 0 and 1 share the first one, 2 and 0 the second
+    assert_faults(&mut pal, &[(0, None), (1, None), (2, None), (0, None)]);
+    // registers: 0 is 1100, 1 is 0100, 2 is 1000. 1 was referenced last before 0 and 2
+    assert_eq!(pal.insert(3), Some(1));
+    // 3 is pending, 2 is 1000 against 1100 for 0
+    assert_eq!(pal.insert(4), Some(2));
+
+    // 0 was hot before 1 and 2 came in: Counter holds on to it, aging lets it go
+    let run = |mut pal: PAL| {
+      [0, 0, 0, 0, 1, 2].iter().for_each(|frame| {
+        pal.insert(*frame);
+      });
+      pal.insert(3)
+    };
+    assert_eq!(run(PAL::new(PALAlgorithm::Counter, 3)), Some(1));
+    assert_eq!(run(PAL::new(PALAlgorithm::Aging, 3)), Some(0));
+  }
+
+  #[test]
+  fn aging_approximates_lru() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(16);
+    let references = (0..5000).map(|_| rng.gen_range(0..24)).collect::<Vec<usize>>();
+
+    let faults = |mut pal: PAL| {
+      references
+        .iter()
+        .filter(|frame| pal.insert(**frame).is_some())
+        .count()
+    };
+    let aging = |bits, interval| {
+      faults(PAL::with_options(
+        PALAlgorithm::Aging,
+        8,
+        &PALOptions {
+          aging_bits: bits,
+          aging_interval: interval,
+          ..PALOptions::default()
+        },
+      ))
+    };
+
+    // a tick per reference and a register longer than any reuse distance is exactly LRU
+    let lru = faults(PAL::new(PALAlgorithm::LRU, 8));
+    assert_eq!(aging(64, 1), lru);
+    // coarser histories drift away from it
+    assert_ne!(aging(2, 8), lru);
+  }
+
+  #[test]
+  fn test_count_based() {
+    let run = |mut pal: PAL, references: &[usize]| {
+      references
+        .iter()
+        .filter_map(|frame| pal.insert(*frame))
+        .collect::<Vec<_>>()
+    };
+    let references = [0, 0, 0, 1, 1, 2, 3, 2, 4];
+
+    // LFU: the new 2 is spared when 3 comes and 1 goes, then 3 is spared in turn and 2 goes
+    assert_eq!(run(PAL::new(PALAlgorithm::Counter, 3), &references), vec![1, 2]);
+    // MFU: 0 goes first, then 1 and 2 tie at two references and the older 1 goes
+    assert_eq!(run(PAL::new(PALAlgorithm::Mfu, 3), &references), vec![0, 1]);
+
+    // 0 was hot early, LFU keeps it forever while the decay lets it go
+    let references = [0, 0, 0, 0, 0, 0, 1, 2, 1, 2, 1, 2, 1, 2, 3];
+    let decay = |interval| {
+      PAL::with_options(
+        PALAlgorithm::LfuDecay,
+        3,
+        &PALOptions {
+          decay_interval: interval,
+          ..PALOptions::default()
+        },
+      )
+    };
+    assert_eq!(run(PAL::new(PALAlgorithm::Counter, 3), &references), vec![1]);
+    assert_eq!(run(decay(4), &references), vec![0]);
+
+    // a new page has the lowest count but survives the next fault
+    let mut pal = PAL::new(PALAlgorithm::Counter, 3);
+    [0, 0, 0, 1, 1, 2].iter().for_each(|frame| {
+      pal.insert(*frame);
+    });
+    assert_eq!(pal.insert(3), Some(1));
+    assert_eq!(pal.insert(2), None);
+  }
+
+  #[test]
+  fn test_wsclock() {
+    let wsclock = |frames, tau| {
+      PAL::with_options(
+        PALAlgorithm::WSClock,
+        frames,
+        &PALOptions {
+          tau,
+          ..PALOptions::default()
+        },
+      )
+    };
+
+    let mut pal = wsclock(3, 2);
+    assert_eq!(pal.reference(0, AccessKind::Write), None);
+    assert_faults(&mut pal, &[(1, None), (2, None)]);
+    // 0 and 1 are out of the window, the write back of 0 is scheduled and the clean 1 goes
+    assert_eq!(pal.insert(3), Some(1));
+    assert_eq!(pal.write_back(), Some(0));
+    assert_eq!(pal.write_back(), None);
+    // 2 is referenced and gets refreshed, 0 is clean by now
+    pal.insert(2);
+    assert_eq!(pal.insert(4), Some(0));
+
+    // everything is in the window: the first clean frame goes
+    let mut pal = wsclock(2, 10);
+    pal.insert(0);
+    pal.insert(1);
+    pal.reference(0, AccessKind::Write);
+    assert_eq!(pal.insert(2), Some(1));
+  }
+
+  #[test]
+  fn test_working_set() {
+    let mut pal = PAL::with_options(
+      PALAlgorithm::WorkingSet,
+      4,
+      &PALOptions {
+        tau: 3,
+        ..PALOptions::default()
+      },
+    );
+
+    pal.insert(0);
+    pal.insert(1);
+    pal.insert(1);
+    assert_eq!(pal.release(), None);
+    // 0 was last referenced three references ago
+    pal.insert(1);
+    assert_eq!(pal.release(), Some(0));
+    pal.on_evict(0);
+    assert_eq!(pal.release(), None);
+  }
+
+  #[test]
+  fn test_arc() {
+    // pages and frames told apart: the frame freed by an eviction is the one reused
+    let mut pal = PAL::new(PALAlgorithm::ARC, 4);
+    let mut frames = HashMap::new();
+    let mut access = |pal: &mut PAL, page: usize| {
+      if let Some(&frame) = frames.get(&page) {
+        pal.on_hit(page, AccessKind::Read, frame);
+        return frame;
+      }
+
+      pal.on_fault(page, AccessKind::Read);
+      let frame = match frames.len() < 4 {
+        true => frames.len(),
+        false => {
+          let frame = pal.choose_victim();
+          pal.on_evict(frame);
+          frame
+        }
+      };
+      frames.retain(|_, other| *other != frame);
+      frames.insert(page, frame);
+      pal.on_load(frame);
+      frame
+    };
+    let state = |pal: &PAL| {
+      pal
+        .parameters()
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect::<Vec<_>>()
+    };
+
+    // 1 and 2 are used twice and move to T2
+    [1, 2, 1, 2].iter().for_each(|page| {
+      access(&mut pal, *page);
+    });
+    assert_eq!(state(&pal), vec![0, 0, 2, 0, 0]);
+
+    // a scan goes through T1 without touching T2
+    (10..16).for_each(|page| {
+      access(&mut pal, page);
+    });
+    assert_eq!(state(&pal), vec![0, 2, 2, 2, 0]);
+    assert_eq!(access(&mut pal, 1), 0);
+
+    // 12 comes back from B1: T1 was too small, p grows and the page goes to T2
+    access(&mut pal, 12);
+    assert_eq!(state(&pal), vec![1, 1, 3, 2, 0]);
+  }
+
+  #[test]
+  fn arc_resists_scans() {
+    let faults = |algorithm| {
+      let mut pal = PAL::new(algorithm, 8);
+      // a hot loop over 6 pages, then a scan of 10 pages never seen again, over and over
+      (0..100)
+        .flat_map(|round| (0..12).map(|i| i % 6).chain((0..10).map(move |i| 1000 + round * 10 + i)))
+        .filter(|page| pal.insert(*page).is_some())
+        .count()
+    };
+
+    // ARC only evicts for the 1000 scanned pages, LRU reloads the hot loop after every scan too
+    assert!(faults(PALAlgorithm::ARC) <= 1000);
+    assert!(faults(PALAlgorithm::LRU) > 1500);
+  }
+
+  #[test]
+  fn lirs_and_two_queue_resist_scans() {
+    let faults = |algorithm| {
+      let mut pal = PAL::new(algorithm, 16);
+      // a hot loop over 6 pages, then a scan of 12 pages never seen again, over and over
+      (0..100)
+        .flat_map(|round| {
+          let scan = (0..12).map(move |i| 1000 + round * 12 + i);
+          (0..12).map(|i| i % 6).chain(scan)
+        })
+        .filter(|page| pal.insert(*page).is_some())
+        .count()
+    };
+
+    // both soon only evict for the 1200 scanned pages, LRU reloads the hot loop after every scan
+    // too. 2Q needs the frames for its A1in and A1out queues to hold the hot loop
+    assert!(faults(PALAlgorithm::LIRS) <= 1200);
+    assert!(faults(PALAlgorithm::TwoQueue) <= 1200);
+    assert!(faults(PALAlgorithm::LRU) > 1700);
+  }
+
+  #[test]
+  fn test_two_queue() {
+    // 8 frames: A1in keeps 2 pages beyond which it evicts, A1out remembers 4
+    let mut pal = PAL::new(PALAlgorithm::TwoQueue, 8);
+    (0..8).for_each(|page| assert_eq!(pal.insert(page), None));
+
+    // a second reference within A1in does not make a page hot, and A1in is evicted in FIFO order
+    pal.insert(0);
+    assert_eq!(pal.insert(8), Some(0));
+    assert_eq!(pal.insert(9), Some(1));
+    assert_eq!(pal.parameters(), vec![("a1in", 8), ("a1out", 2), ("am", 0)]);
+
+    // 0 is back while remembered in A1out, so it goes to Am and outlives the pages seen once
+    assert_eq!(pal.insert(0), Some(2));
+    (10..20).for_each(|page| assert_ne!(pal.insert(page), Some(0)));
+    assert_eq!(pal.parameters(), vec![("a1in", 7), ("a1out", 4), ("am", 1)]);
+  }
+
+  #[test]
+  fn test_lirs() {
+    // 4 frames: 3 for LIR pages, 1 for HIR ones
+    let mut pal = PAL::new(PALAlgorithm::LIRS, 4);
+    assert_faults(&mut pal, &[(0, None), (1, None), (2, None), (3, None)]);
+    assert_eq!(pal.parameters(), vec![("lir", 3), ("hir", 1), ("ghosts", 0), ("stack", 4)]);
+
+    // the HIR page 3 goes first, and stays in the stack as a ghost
+    assert_eq!(pal.insert(4), Some(3));
+    assert_eq!(pal.parameters(), vec![("lir", 3), ("hir", 1), ("ghosts", 1), ("stack", 5)]);
+
+    // 3 comes back sooner than 0 was reused: it becomes LIR, and 0 is demoted to HIR
+    assert_eq!(pal.insert(3), Some(4));
+    assert_eq!(pal.parameters(), vec![("lir", 3), ("hir", 1), ("ghosts", 1), ("stack", 4)]);
+    assert_eq!(pal.insert(5), Some(0));
+
+    // a loop one page larger than memory: LRU always misses, LIRS keeps the LIR pages
+    let faults = |algorithm| {
+      let mut pal = PAL::new(algorithm, 8);
+      (0..900)
+        .map(|i| i % 9)
+        .filter(|page| pal.insert(*page).is_some())
+        .count()
+    };
+    assert_eq!(faults(PALAlgorithm::LRU), 892);
+    assert!(faults(PALAlgorithm::LIRS) < 250);
+    // CLOCK-Pro only approximates it, its cold share swings around the loop
+    assert!(faults(PALAlgorithm::ClockPro) < 400);
+  }
+
+  #[test]
+  fn test_random() {
+    let references = (0..200).map(|i| (i * 7) % 11).collect::<Vec<usize>>();
+    let victims = |seed| {
+      let mut pal = PAL::with_options(
+        PALAlgorithm::Random,
+        4,
+        &PALOptions {
+          seed,
+          ..PALOptions::default()
+        },
+      );
+      let mut loaded = std::collections::HashSet::new();
+      references
+        .iter()
+        .filter_map(|frame| {
+          let victim = pal.insert(*frame);
+          // only loaded frames go, and only once memory is full
+          match victim {
+            Some(victim) => assert!(loaded.remove(&victim)),
+            None => assert!(loaded.contains(frame) || loaded.len() < 4),
+          }
+          loaded.insert(*frame);
+          victim
+        })
+        .collect::<Vec<_>>()
+    };
+
+    // the same seed evicts the same frames, another one does not
+    assert_eq!(victims(7), victims(7));
+    assert_ne!(victims(7), victims(8));
+  }
+
+  #[test]
+  fn test_script() {
+    let script = |source: &str| {
+      let policy = script::ScriptPolicy::compile("policy.rhai", source);
+      policy.map_err(|error| format!("{error:#}"))
+    };
+    let run = |policy, references: &[usize]| {
+      let options = PALOptions {
+        policy_file: Some(policy),
+        ..PALOptions::default()
+      };
+      let mut pal = PAL::with_options(PALAlgorithm::Script, 4, &options);
+      let victims = references
+        .iter()
+        .filter_map(|frame| pal.insert(*frame))
+        .collect::<Vec<_>>();
+      (victims, pal.error().map(str::to_string))
+    };
+
+    // LRU over the resident set the script is shown, counting its victims in `this`
+    let lru = script(
+      r#"
+      fn init(frames) {
+        this.victims = 0;
+      }
+
+      fn choose_victim(resident) {
+        let victim = resident[0];
+        for entry in resident {
+          if entry.last_use < victim.last_use {
+            victim = entry;
+          }
+        }
+        this.victims += 1;
+        victim.frame
+      }
+      "#,
+    )
+    .unwrap();
+    let references = (0..300).map(|i| (i * i + 3 * i) % 9).collect::<Vec<usize>>();
+    let mut pal = PAL::new(PALAlgorithm::LRU, 4);
+    let expected = references
+      .iter()
+      .filter_map(|frame| pal.insert(*frame))
+      .collect::<Vec<_>>();
+    assert_eq!(run(lru, &references), (expected, None));
+
+    // the example policy keeps its own queue in `this`
+    let second_chance = script::ScriptPolicy::load("assets/policies/second_chance.rhai").unwrap();
+    let mut pal = PAL::new(PALAlgorithm::SecondChance, 4);
+    let expected = references
+      .iter()
+      .filter_map(|frame| pal.insert(*frame))
+      .collect::<Vec<_>>();
+    assert_eq!(run(second_chance, &references), (expected, None));
+
+    // errors point at the script line
+    let error = script("fn choose_victim(resident) {\n  let victim = ;\n}").unwrap_err();
+    assert!(error.starts_with("policy.rhai:2: "), "{error}");
+    let error = script("fn choose_victim(resident) { 0 }\nfn on_hit(access) {}").unwrap_err();
+    assert_eq!(error, "policy.rhai: `on_hit` takes 2 parameters, not 1");
+    assert!(script("fn on_hit(access, frame) {}").is_err());
+
+    // a failing script is stopped, the oldest frames go and the run reports the error
+    let failing = script("fn choose_victim(resident) {\n  resident[10].frame\n}").unwrap();
+    let (victims, error) = run(failing, &[0, 1, 2, 3, 4, 5]);
+    assert_eq!(victims, vec![0, 1]);
+    let error = error.unwrap();
+    assert!(error.starts_with("policy.rhai:2: in `choose_victim`: "), "{error}");
+
+    let stray = script("fn choose_victim(resident) { 7 }").unwrap();
+    let (victims, error) = run(stray, &[0, 1, 2, 3, 4]);
+    assert_eq!(victims, vec![0]);
+    assert_eq!(
+      error.as_deref(),
+      Some("policy.rhai: `choose_victim` returned frame 7, which holds no page")
+    );
+  }
+
+  #[test]
+  fn test_clock_pro() {
+    // 3 frames: pages are loaded cold, in their test period, and one frame is kept for them
+    let mut pal = PAL::new(PALAlgorithm::ClockPro, 3);
+    assert_faults(&mut pal, &[(0, None), (1, None), (2, None)]);
+    pal.insert(0);
+
+    // 0 was referenced during its test period and turns hot, 1 goes and stays on the clock
+    assert_eq!(pal.insert(3), Some(1));
+    assert_eq!(pal.parameters(), vec![("hot", 1), ("cold", 2), ("test", 1), ("cold_target", 1)]);
+
+    // 1 is back within its test period: it comes in hot, and the cold share grows
+    assert_eq!(pal.insert(1), Some(2));
+    assert_eq!(pal.parameters(), vec![("hot", 2), ("cold", 1), ("test", 1), ("cold_target", 2)]);
+
+    // one hot page too many: HAND_hot demotes 0, which has no test period left to save it
+    assert_eq!(pal.insert(4), Some(3));
+    assert_eq!(pal.parameters(), vec![("hot", 1), ("cold", 2), ("test", 2), ("cold_target", 2)]);
+    assert_eq!(pal.insert(5), Some(0));
+  }
+
+  #[test]
+  fn test_sieve() {
+    let mut pal = PAL::new(PALAlgorithm::Sieve, 3);
+    assert_faults(&mut pal, &[(0, None), (1, None), (2, None)]);
+
+    // the hand starts at the oldest frame and spares the visited 0
+    pal.insert(0);
+    assert_eq!(pal.insert(3), Some(1));
+
+    // the hand goes on from 2 rather than the oldest frame, and reaches the new frame 3 first
+    pal.insert(2);
+    assert_eq!(pal.insert(4), Some(3));
+
+    // then wraps around: 0 was not moved when spared, and its visited bit is gone
+    assert_eq!(pal.insert(5), Some(0));
+    assert_eq!(pal.insert(6), Some(2));
+  }
+
+  #[test]
+  fn test_s3_fifo() {
+    // 4 frames: S is evicted from while it holds a page, G remembers 3
+    let mut pal = PAL::new(PALAlgorithm::S3Fifo, 4);
+    assert_faults(&mut pal, &[(0, None), (1, None), (2, None), (3, None)]);
+
+    // referenced pages leave S for M, and with S empty, M loses 0, not referenced since
+    assert_faults(&mut pal, &[(0, None), (1, None), (2, None), (3, None)]);
+    assert_eq!(pal.insert(4), Some(0));
+    assert_eq!(pal.parameters(), vec![("small", 1), ("main", 3), ("ghost", 0)]);
+
+    // 4 was not referenced in S, and is remembered in G
+    pal.insert(1);
+    assert_eq!(pal.insert(5), Some(4));
+
+    // back while in G, 4 goes to M
+    assert_eq!(pal.insert(4), Some(5));
+    assert_eq!(pal.parameters(), vec![("small", 0), ("main", 4), ("ghost", 1)]);
+
+    // M reinserts 1, and evicts 2
+    assert_eq!(pal.insert(6), Some(2));
+    assert_eq!(pal.parameters(), vec![("small", 1), ("main", 3), ("ghost", 1)]);
   }
 }
@@ -0,0 +1,106 @@
+use std::{
+  cmp::Reverse,
+  collections::{BTreeSet, HashMap},
+};
+
+use super::{Access, PALTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OptimalPALTableEntry {
+  /// Position in the trace of the next reference to the page held by this frame
+  pub next_use: usize,
+  /// Load order of the frame, the oldest one goes first among pages never used again
+  pub loaded_at: usize,
+}
+
+/// Belady's optimal (OPT/MIN) replacement. Evicts the frame whose page is referenced furthest in
+/// the future, which requires the next-use position of every reference of the trace beforehand.
+#[derive(Debug, Clone)]
+pub struct OptimalPALTable {
+  pub entries: HashMap<usize, OptimalPALTableEntry>,
+  /// Frames ordered by next use, the one referenced furthest in the future last
+  pub queue: BTreeSet<(usize, Reverse<usize>, usize)>,
+  pub loads: usize,
+  /// `next_uses[i]` is the position of the next reference to the page referenced at `i`
+  pub next_uses: Vec<usize>,
+  /// Number of references seen so far, i.e. the position of the next reference in the trace
+  pub position: usize,
+}
+
+impl OptimalPALTable {
+  fn next_use(&self) -> usize {
+    self.next_uses.get(self.position).copied().unwrap_or(usize::MAX)
+  }
+
+  fn enqueue(&mut self, frame: usize, entry: OptimalPALTableEntry) {
+    self.entries.insert(frame, entry);
+    self
+      .queue
+      .insert((entry.next_use, Reverse(entry.loaded_at), frame));
+  }
+}
+
+impl PALTable for OptimalPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if let Some(entry) = self.entries.remove(&frame) {
+      self
+        .queue
+        .remove(&(entry.next_use, Reverse(entry.loaded_at), frame));
+      self.enqueue(
+        frame,
+        OptimalPALTableEntry {
+          next_use: self.next_use(),
+          ..entry
+        },
+      );
+    }
+    self.position += 1;
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let (_, _, frame) = *self.queue.last().unwrap();
+    frame
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    if let Some(entry) = self.entries.remove(&frame) {
+      self
+        .queue
+        .remove(&(entry.next_use, Reverse(entry.loaded_at), frame));
+    }
+  }
+
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    let entry = OptimalPALTableEntry {
+      next_use: self.next_use(),
+      loaded_at: self.loads,
+    };
+    self.loads += 1;
+    self.enqueue(frame, entry);
+    self.position += 1;
+  }
+
+  fn resident(&self) -> usize {
+    self.entries.len()
+  }
+
+  fn set_lookahead(&mut self, next_uses: Vec<usize>) {
+    self.next_uses = next_uses;
+    self.position = 0;
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("Optimal PAL Table {{");
+    for (next_use, _, frame) in self.queue.iter() {
+      match *next_use {
+        usize::MAX => println!("   [{frame}]: never"),
+        next_use => println!("   [{frame}]: {next_use}"),
+      }
+    }
+    println!("}}");
+  }
+}
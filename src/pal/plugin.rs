@@ -0,0 +1,411 @@
+use std::{
+  collections::HashMap,
+  ffi::{c_char, c_void, CStr},
+  hash::{Hash, Hasher},
+  path::Path,
+  ptr::NonNull,
+  sync::{Mutex, OnceLock},
+};
+
+use libloading::Library;
+use once_cell::sync::Lazy;
+
+use super::{Access, PALTable};
+
+/// Version of `PluginVTable`, a plugin built for another one is refused
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Symbol every plugin exports: `extern "C" fn() -> *const PluginVTable`
+pub const PLUGIN_SYMBOL: &str = "pal_plugin_vtable";
+
+/// Room `parameters` is given, extra parameters are left out
+pub const PLUGIN_MAX_PARAMETERS: usize = 16;
+
+/// A reference as plugins see it
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginAccess {
+  pub page: usize,
+  pub write: bool,
+  pub time: u64,
+}
+
+impl From<&Access> for PluginAccess {
+  fn from(access: &Access) -> Self {
+    Self {
+      page: access.page,
+      write: access.kind.is_write(),
+      time: access.time,
+    }
+  }
+}
+
+/// The C ABI of `PALTable`. A plugin keeps its state behind an opaque pointer made by `new`, which
+/// every other entry takes first, and must let states move between threads. The `Option` entries
+/// may be null:
+/// - `release` and `write_back` write a frame and return true, or return false.
+/// - `set_lookahead` gets the next-use position of every reference, to copy before returning.
+/// - `parameters` writes up to `capacity` names and values and returns how many it wrote. Names are
+///   NUL terminated and live as long as the library.
+/// - `error` returns the NUL terminated message of the first error, or null. It is copied out.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PluginVTable {
+  pub abi_version: u32,
+  /// NUL terminated name of the policy
+  pub name: unsafe extern "C" fn() -> *const c_char,
+  pub new: unsafe extern "C" fn(capacity: usize) -> *mut c_void,
+  pub clone: unsafe extern "C" fn(state: *const c_void) -> *mut c_void,
+  pub drop: unsafe extern "C" fn(state: *mut c_void),
+  pub on_hit: unsafe extern "C" fn(state: *mut c_void, access: *const PluginAccess, frame: usize),
+  pub on_fault: Option<unsafe extern "C" fn(state: *mut c_void, access: *const PluginAccess)>,
+  pub choose_victim: unsafe extern "C" fn(state: *mut c_void) -> usize,
+  pub on_evict: unsafe extern "C" fn(state: *mut c_void, frame: usize),
+  pub on_load: unsafe extern "C" fn(state: *mut c_void, access: *const PluginAccess, frame: usize),
+  pub resident: unsafe extern "C" fn(state: *const c_void) -> usize,
+  pub release: Option<unsafe extern "C" fn(state: *mut c_void, frame: *mut usize) -> bool>,
+  pub write_back: Option<unsafe extern "C" fn(state: *mut c_void, frame: *mut usize) -> bool>,
+  pub set_lookahead:
+    Option<unsafe extern "C" fn(state: *mut c_void, next_uses: *const usize, len: usize)>,
+  pub parameters: Option<
+    unsafe extern "C" fn(
+      state: *const c_void,
+      names: *mut *const c_char,
+      values: *mut usize,
+      capacity: usize,
+    ) -> usize,
+  >,
+  pub error: Option<unsafe extern "C" fn(state: *const c_void) -> *const c_char>,
+}
+
+/// A policy loaded from a dynamic library. Libraries stay loaded until the process exits, so
+/// plugins are handed out as `&'static` and `PALAlgorithm` stays `Copy`.
+#[derive(Debug)]
+pub struct Plugin {
+  /// Path the library was loaded from
+  pub path: String,
+  pub name: String,
+  pub vtable: PluginVTable,
+  _library: Library,
+}
+
+/// Plugins loaded so far, by path, so that a library is only loaded once
+static PLUGINS: Lazy<Mutex<HashMap<String, &'static Plugin>>> = Lazy::new(Default::default);
+
+impl Plugin {
+  pub fn load(path: &str) -> anyhow::Result<&'static Plugin> {
+    let mut plugins = PLUGINS.lock().unwrap();
+    if let Some(plugin) = plugins.get(path) {
+      return Ok(plugin);
+    }
+
+    // a bare file name would be looked up in the system library paths
+    let file = match path.contains(std::path::MAIN_SEPARATOR) {
+      true => Path::new(path).to_path_buf(),
+      false => Path::new(".").join(path),
+    };
+    // SAFETY: loading runs the initializers of the library, the user's own code. The errors of the
+    // dynamic loader already name the library
+    let library = unsafe { Library::new(file)? };
+    let vtable = unsafe {
+      let entry =
+        library.get::<unsafe extern "C" fn() -> *const PluginVTable>(PLUGIN_SYMBOL.as_bytes())?;
+      let vtable = entry();
+      anyhow::ensure!(!vtable.is_null(), "{path}: `{PLUGIN_SYMBOL}` returned no vtable");
+      // only the version is read before knowing the layout
+      let abi_version = *(vtable as *const u32);
+      anyhow::ensure!(
+        abi_version == PLUGIN_ABI_VERSION,
+        "{path}: plugin ABI version {abi_version}, expected {PLUGIN_ABI_VERSION}"
+      );
+      *vtable
+    };
+    let name = unsafe { CStr::from_ptr((vtable.name)()) }
+      .to_string_lossy()
+      .into_owned();
+
+    let plugin = Box::leak(Box::new(Plugin {
+      path: path.to_string(),
+      name,
+      vtable,
+      _library: library,
+    }));
+    plugins.insert(path.to_string(), plugin);
+    Ok(plugin)
+  }
+}
+
+impl PartialEq for Plugin {
+  fn eq(&self, other: &Self) -> bool {
+    self.path == other.path
+  }
+}
+
+impl Eq for Plugin {}
+
+impl Hash for Plugin {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.path.hash(state);
+  }
+}
+
+/// A `PALTable` forwarding every event to a plugin
+#[derive(Debug)]
+pub struct PluginPALTable {
+  pub plugin: &'static Plugin,
+  state: NonNull<c_void>,
+  /// First error of the plugin, copied out of it
+  error: OnceLock<String>,
+}
+
+// SAFETY: the state is only reached through `&mut self`, and the ABI asks plugins for states
+// that can move between threads
+unsafe impl Send for PluginPALTable {}
+
+impl PluginPALTable {
+  pub fn new(plugin: &'static Plugin, capacity: usize) -> Self {
+    let state = unsafe { (plugin.vtable.new)(capacity) };
+    Self {
+      plugin,
+      state: NonNull::new(state).expect("the plugin made no state"),
+      error: OnceLock::new(),
+    }
+  }
+}
+
+impl Clone for PluginPALTable {
+  fn clone(&self) -> Self {
+    let state = unsafe { (self.plugin.vtable.clone)(self.state.as_ptr()) };
+    Self {
+      plugin: self.plugin,
+      state: NonNull::new(state).expect("the plugin made no state"),
+      error: self.error.clone(),
+    }
+  }
+}
+
+impl Drop for PluginPALTable {
+  fn drop(&mut self) {
+    unsafe { (self.plugin.vtable.drop)(self.state.as_ptr()) }
+  }
+}
+
+impl PALTable for PluginPALTable {
+  fn on_hit(&mut self, access: &Access, frame: usize) {
+    let access = PluginAccess::from(access);
+    unsafe { (self.plugin.vtable.on_hit)(self.state.as_ptr(), &access, frame) }
+  }
+
+  fn on_fault(&mut self, access: &Access) {
+    if let Some(on_fault) = self.plugin.vtable.on_fault {
+      let access = PluginAccess::from(access);
+      unsafe { on_fault(self.state.as_ptr(), &access) }
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    unsafe { (self.plugin.vtable.choose_victim)(self.state.as_ptr()) }
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    unsafe { (self.plugin.vtable.on_evict)(self.state.as_ptr(), frame) }
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    let access = PluginAccess::from(access);
+    unsafe { (self.plugin.vtable.on_load)(self.state.as_ptr(), &access, frame) }
+  }
+
+  fn resident(&self) -> usize {
+    unsafe { (self.plugin.vtable.resident)(self.state.as_ptr()) }
+  }
+
+  fn release(&mut self) -> Option<usize> {
+    let release = self.plugin.vtable.release?;
+    let mut frame = 0;
+    unsafe { release(self.state.as_ptr(), &mut frame) }.then_some(frame)
+  }
+
+  fn write_back(&mut self) -> Option<usize> {
+    let write_back = self.plugin.vtable.write_back?;
+    let mut frame = 0;
+    unsafe { write_back(self.state.as_ptr(), &mut frame) }.then_some(frame)
+  }
+
+  fn set_lookahead(&mut self, next_uses: Vec<usize>) {
+    if let Some(set_lookahead) = self.plugin.vtable.set_lookahead {
+      unsafe { set_lookahead(self.state.as_ptr(), next_uses.as_ptr(), next_uses.len()) }
+    }
+  }
+
+  fn parameters(&self) -> Vec<(&'static str, usize)> {
+    let Some(parameters) = self.plugin.vtable.parameters else {
+      return vec![];
+    };
+    let mut names = [std::ptr::null(); PLUGIN_MAX_PARAMETERS];
+    let mut values = [0; PLUGIN_MAX_PARAMETERS];
+    let count = unsafe {
+      parameters(
+        self.state.as_ptr(),
+        names.as_mut_ptr(),
+        values.as_mut_ptr(),
+        PLUGIN_MAX_PARAMETERS,
+      )
+    };
+
+    // SAFETY: the names live as long as the library, which is never unloaded
+    names
+      .iter()
+      .zip(values)
+      .take(count.min(PLUGIN_MAX_PARAMETERS))
+      .map(|(&name, value)| (unsafe { CStr::from_ptr(name) }.to_str().unwrap_or("?"), value))
+      .collect()
+  }
+
+  fn error(&self) -> Option<&str> {
+    if self.error.get().is_none() {
+      let error = unsafe { (self.plugin.vtable.error?)(self.state.as_ptr()) };
+      if error.is_null() {
+        return None;
+      }
+      let error = unsafe { CStr::from_ptr(error) }.to_string_lossy();
+      let _ = self.error.set(format!("{}: {error}", self.plugin.path));
+    }
+    self.error.get().map(String::as_str)
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("Plugin PAL Table ({}, {}) {{", self.plugin.name, self.plugin.path);
+    println!("   resident: {}", self.resident());
+    println!("}}");
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::pal::{PALAlgorithm, PAL};
+
+  use super::*;
+
+  /// FIFO scheduling the write back of the frame after its victim, failing once it wrote back
+  #[derive(Debug, Clone, Default)]
+  struct Hooks {
+    frames: Vec<usize>,
+    lookahead: usize,
+    write_backs: Vec<usize>,
+    failed: bool,
+  }
+
+  unsafe fn hooks<'a>(state: *const c_void) -> &'a mut Hooks {
+    &mut *(state as *mut Hooks)
+  }
+
+  unsafe extern "C" fn name() -> *const c_char {
+    c"hooks".as_ptr()
+  }
+  unsafe extern "C" fn new(_capacity: usize) -> *mut c_void {
+    Box::into_raw(Box::<Hooks>::default()) as *mut c_void
+  }
+  unsafe extern "C" fn clone(state: *const c_void) -> *mut c_void {
+    Box::into_raw(Box::new(hooks(state).clone())) as *mut c_void
+  }
+  unsafe extern "C" fn drop(state: *mut c_void) {
+    std::mem::drop(Box::from_raw(state as *mut Hooks));
+  }
+  unsafe extern "C" fn on_hit(_state: *mut c_void, _access: *const PluginAccess, _frame: usize) {}
+  unsafe extern "C" fn choose_victim(state: *mut c_void) -> usize {
+    let hooks = hooks(state);
+    hooks.write_backs.push(hooks.frames[1]);
+    hooks.frames[0]
+  }
+  unsafe extern "C" fn on_evict(state: *mut c_void, frame: usize) {
+    hooks(state).frames.retain(|&other| other != frame);
+  }
+  unsafe extern "C" fn on_load(state: *mut c_void, _access: *const PluginAccess, frame: usize) {
+    hooks(state).frames.push(frame);
+  }
+  unsafe extern "C" fn resident(state: *const c_void) -> usize {
+    hooks(state).frames.len()
+  }
+  unsafe extern "C" fn write_back(state: *mut c_void, frame: *mut usize) -> bool {
+    let hooks = hooks(state);
+    let Some(next) = hooks.write_backs.pop() else {
+      return false;
+    };
+    hooks.failed = true;
+    *frame = next;
+    true
+  }
+  unsafe extern "C" fn set_lookahead(state: *mut c_void, _next_uses: *const usize, len: usize) {
+    hooks(state).lookahead = len;
+  }
+  unsafe extern "C" fn parameters(
+    state: *const c_void,
+    names: *mut *const c_char,
+    values: *mut usize,
+    _capacity: usize,
+  ) -> usize {
+    *names = c"lookahead".as_ptr();
+    *values = hooks(state).lookahead;
+    1
+  }
+  unsafe extern "C" fn error(state: *const c_void) -> *const c_char {
+    match hooks(state).failed {
+      true => c"out of frames".as_ptr(),
+      false => std::ptr::null(),
+    }
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn optional_entries() {
+    // the test binary stands in for the library
+    let plugin = Box::leak(Box::new(Plugin {
+      path: "hooks".to_string(),
+      name: "hooks".to_string(),
+      vtable: PluginVTable {
+        abi_version: PLUGIN_ABI_VERSION,
+        name,
+        new,
+        clone,
+        drop,
+        on_hit,
+        on_fault: None,
+        choose_victim,
+        on_evict,
+        on_load,
+        resident,
+        release: None,
+        write_back: Some(write_back),
+        set_lookahead: Some(set_lookahead),
+        parameters: Some(parameters),
+        error: Some(error),
+      },
+      _library: libloading::os::unix::Library::this().into(),
+    }));
+
+    let mut pal = PAL::new(PALAlgorithm::Plugin(plugin), 2);
+    pal.set_lookahead(vec![3, 4, 5]);
+    assert_eq!(pal.parameters(), vec![("lookahead", 3)]);
+    pal.insert(0);
+    pal.insert(1);
+    assert_eq!(pal.error(), None);
+
+    assert_eq!(pal.insert(2), Some(0));
+    assert_eq!(pal.write_back(), Some(1));
+    assert_eq!(pal.write_back(), None);
+    assert_eq!(pal.error(), Some("hooks: out of frames"));
+    assert!(crate::mmu::tlb::TLB::new(2, 2, PALAlgorithm::Plugin(plugin)).is_err());
+  }
+
+  #[test]
+  fn missing_plugin() {
+    let error = Plugin::load("/nonexistent/libpolicy.so").unwrap_err();
+    assert!(format!("{error:#}").starts_with("/nonexistent/libpolicy.so: "));
+    assert!("plugin:/nonexistent/libpolicy.so".parse::<PALAlgorithm>().is_err());
+  }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{Access, PALTable};
+
+/// Random replacement: the victim is drawn uniformly among the loaded frames. The generator is
+/// seeded, so two runs with the same seed evict the same frames.
+#[derive(Debug, Clone)]
+pub struct RandomPALTable {
+  pub frames: Vec<usize>,
+  /// Index of every loaded frame in `frames`
+  pub positions: HashMap<usize, usize>,
+  pub rng: StdRng,
+}
+
+impl RandomPALTable {
+  pub fn new(capacity: usize, seed: u64) -> Self {
+    Self {
+      frames: Vec::with_capacity(capacity),
+      positions: HashMap::with_capacity(capacity),
+      rng: StdRng::seed_from_u64(seed),
+    }
+  }
+}
+
+impl PALTable for RandomPALTable {
+  fn on_hit(&mut self, _access: &Access, _frame: usize) {}
+
+  fn choose_victim(&mut self) -> usize {
+    self.frames[self.rng.gen_range(0..self.frames.len())]
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    let Some(index) = self.positions.remove(&frame) else {
+      return;
+    };
+    self.frames.swap_remove(index);
+    if let Some(&moved) = self.frames.get(index) {
+      self.positions.insert(moved, index);
+    }
+  }
+
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    self.positions.insert(frame, self.frames.len());
+    self.frames.push(frame);
+  }
+
+  fn resident(&self) -> usize {
+    self.frames.len()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("Random PAL Table {{");
+    for frame in self.frames.iter() {
+      println!("   [{frame}]");
+    }
+    println!("}}");
+  }
+}
@@ -0,0 +1,135 @@
+use super::{list::FrameList, Access, PALTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct S3FifoEntry {
+  pub page: usize,
+  /// References since the page entered its queue, saturating at 3
+  pub freq: u8,
+}
+
+/// S3-FIFO (Yang et al.). New pages enter the small FIFO S, a tenth of the frames. Leaving S, a
+/// page referenced again moves to the main FIFO M, and one that was not is evicted and
+/// remembered in the ghost FIFO G, so that it goes straight to M if it comes back soon. M
+/// reinserts its referenced pages, spending one unit of their frequency each time.
+#[derive(Debug, Clone)]
+pub struct S3FifoPALTable {
+  /// S, by frame, oldest first
+  pub small: FrameList<S3FifoEntry>,
+  /// M, by frame, oldest first
+  pub main: FrameList<S3FifoEntry>,
+  /// G, pages evicted from S, oldest first
+  pub ghost: FrameList<()>,
+  /// Whether the page of the fault being served was found in G
+  pub returning: bool,
+  /// Size S is evicted from, a tenth of the frames
+  pub small_capacity: usize,
+  /// Size of G, as many pages as M holds
+  pub ghost_capacity: usize,
+}
+
+impl S3FifoPALTable {
+  pub fn new(capacity: usize) -> Self {
+    let small_capacity = (capacity / 10).max(1);
+    Self {
+      small: FrameList::with_capacity(capacity),
+      main: FrameList::with_capacity(capacity),
+      ghost: FrameList::with_capacity(capacity),
+      returning: false,
+      small_capacity,
+      ghost_capacity: capacity.saturating_sub(small_capacity).max(1),
+    }
+  }
+
+  fn remember(&mut self, page: usize) {
+    self.ghost.push_back(page, ());
+    if self.ghost.len() > self.ghost_capacity {
+      self.ghost.pop_front();
+    }
+  }
+}
+
+impl PALTable for S3FifoPALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if let Some(entry) = self.small.get_mut(frame).or(self.main.get_mut(frame)) {
+      entry.freq = (entry.freq + 1).min(3);
+    }
+  }
+
+  fn on_fault(&mut self, access: &Access) {
+    // looked up before evicting, which may push the page out of G
+    self.returning = self.ghost.remove(access.page).is_some();
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    loop {
+      if self.small.len() >= self.small_capacity || self.main.is_empty() {
+        let frame = self.small.front().unwrap();
+        let entry = *self.small.get(frame).unwrap();
+        if entry.freq == 0 {
+          return frame;
+        }
+        self.small.remove(frame);
+        self.main.push_back(frame, S3FifoEntry { freq: 0, ..entry });
+      } else {
+        let frame = self.main.front().unwrap();
+        let entry = *self.main.get(frame).unwrap();
+        if entry.freq == 0 {
+          return frame;
+        }
+        let freq = entry.freq - 1;
+        self.main.move_to_back(frame);
+        self.main.get_mut(frame).unwrap().freq = freq;
+      }
+    }
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    match self.small.remove(frame) {
+      Some(entry) => self.remember(entry.page),
+      None => {
+        self.main.remove(frame);
+      }
+    }
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    let entry = S3FifoEntry {
+      page: access.page,
+      freq: 0,
+    };
+    match std::mem::take(&mut self.returning) {
+      true => self.main.push_back(frame, entry),
+      false => self.small.push_back(frame, entry),
+    }
+  }
+
+  fn resident(&self) -> usize {
+    self.small.len() + self.main.len()
+  }
+
+  fn parameters(&self) -> Vec<(&'static str, usize)> {
+    vec![
+      ("small", self.small.len()),
+      ("main", self.main.len()),
+      ("ghost", self.ghost.len()),
+    ]
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    let pages = |queue: &FrameList<S3FifoEntry>| {
+      queue
+        .iter()
+        .map(|(_, entry)| (entry.page, entry.freq))
+        .collect::<Vec<_>>()
+    };
+    println!("S3-FIFO PAL Table {{");
+    println!("   S: {:?}", pages(&self.small));
+    println!("   M: {:?}", pages(&self.main));
+    println!("   G: {:?}", self.ghost.iter().map(|(page, _)| page).collect::<Vec<_>>());
+    println!("}}");
+  }
+}
@@ -0,0 +1,282 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use rhai::{
+  Array, CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Map, Position, Scope, AST, INT,
+};
+
+use super::{list::FrameList, Access, PALTable};
+
+/// Callbacks a policy script may define, with their parameter count. Only `choose_victim` is
+/// required.
+const CALLBACKS: [(&str, usize); 6] = [
+  ("init", 1),
+  ("on_hit", 2),
+  ("on_fault", 1),
+  ("choose_victim", 1),
+  ("on_evict", 1),
+  ("on_load", 2),
+];
+
+/// Operations a single callback may run, so that a script stuck in a loop fails instead of
+/// hanging the simulation
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A replacement policy written in Rhai, compiled once and shared by every table running it.
+/// Scripts have no access to the file system or the environment, `print` goes to stderr.
+#[derive(Debug, Clone)]
+pub struct ScriptPolicy {
+  /// File the script was read from
+  pub path: String,
+  pub engine: Arc<Engine>,
+  pub ast: Arc<AST>,
+  /// Callbacks the script defines
+  pub callbacks: Vec<&'static str>,
+}
+
+impl ScriptPolicy {
+  pub fn load(path: &str) -> anyhow::Result<Self> {
+    let source = std::fs::read_to_string(path)?;
+    Self::compile(path, &source)
+  }
+
+  pub fn compile(path: &str, source: &str) -> anyhow::Result<Self> {
+    let mut engine = Engine::new();
+    engine
+      .set_max_operations(MAX_OPERATIONS)
+      .on_print(|text| eprintln!("{text}"))
+      .disable_symbol("eval");
+
+    let ast = engine
+      .compile(source)
+      .map_err(|error| located(path, error.1, error.0.to_string()))?;
+
+    let functions = ast
+      .iter_functions()
+      .map(|function| (function.name.to_string(), function.params.len()))
+      .collect::<HashMap<_, _>>();
+    let mut callbacks = Vec::new();
+    for (name, params) in CALLBACKS {
+      match functions.get(name) {
+        Some(&count) if count != params => {
+          anyhow::bail!("{path}: `{name}` takes {params} parameters, not {count}")
+        }
+        Some(_) => callbacks.push(name),
+        None if name == "choose_victim" => {
+          anyhow::bail!("{path}: the policy does not define `choose_victim(resident)`")
+        }
+        None => {}
+      }
+    }
+
+    Ok(Self {
+      path: path.to_string(),
+      engine: Arc::new(engine),
+      ast: Arc::new(ast),
+      callbacks,
+    })
+  }
+}
+
+impl FromStr for ScriptPolicy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::load(s).map_err(|error| format!("{error:#}"))
+  }
+}
+
+/// `path:line: message`, the way trace errors are reported
+fn located(path: &str, position: Position, message: String) -> anyhow::Error {
+  match position.line() {
+    Some(line) => anyhow::anyhow!(message).context(format!("{path}:{line}")),
+    None => anyhow::anyhow!(message).context(path.to_string()),
+  }
+}
+
+/// What the script knows of a loaded frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptEntry {
+  pub page: usize,
+  /// Tick of the load
+  pub loaded: u64,
+  /// Tick of the latest reference
+  pub last_use: u64,
+  /// References since the load, the load included
+  pub uses: u64,
+  pub modified: bool,
+}
+
+impl ScriptEntry {
+  fn to_map(self, frame: usize) -> Map {
+    Map::from_iter([
+      ("frame".into(), (frame as INT).into()),
+      ("page".into(), (self.page as INT).into()),
+      ("loaded".into(), (self.loaded as INT).into()),
+      ("last_use".into(), (self.last_use as INT).into()),
+      ("uses".into(), (self.uses as INT).into()),
+      ("modified".into(), self.modified.into()),
+    ])
+  }
+}
+
+fn access_map(access: &Access) -> Map {
+  Map::from_iter([
+    ("page".into(), (access.page as INT).into()),
+    ("write".into(), access.kind.is_write().into()),
+    ("time".into(), (access.time as INT).into()),
+  ])
+}
+
+/// Runs the callbacks of a `ScriptPolicy`. The table keeps the resident set and hands the script
+/// a copy of it, as an array of maps in load order, when a victim is needed: the script can only
+/// answer with one of its frames. The script's own state is the `this` of every callback, an
+/// object map set up by `init(frames)`.
+///
+/// A callback failing, or a victim that is not resident, stops the script: the error is kept and
+/// the run stops at that reference, the oldest frame standing in for the victim.
+#[derive(Debug, Clone)]
+pub struct ScriptPALTable {
+  pub policy: ScriptPolicy,
+  /// `this` of the callbacks
+  pub state: Dynamic,
+  /// Loaded frames, oldest first
+  pub entries: FrameList<ScriptEntry>,
+  /// First error of the script, `path:line: message`
+  pub error: Option<String>,
+}
+
+impl ScriptPALTable {
+  pub fn new(capacity: usize, policy: ScriptPolicy) -> Self {
+    let mut table = Self {
+      policy,
+      state: Map::new().into(),
+      entries: FrameList::with_capacity(capacity),
+      error: None,
+    };
+    table.call("init", (capacity as INT,));
+    table
+  }
+
+  /// Calls `name` if the script defines it, `None` once the script failed
+  fn call(&mut self, name: &str, args: impl FuncArgs) -> Option<Dynamic> {
+    if self.error.is_some() || !self.policy.callbacks.contains(&name) {
+      return None;
+    }
+
+    let options = CallFnOptions::new()
+      .eval_ast(false)
+      .bind_this_ptr(&mut self.state);
+    let result = self.policy.engine.call_fn_with_options::<Dynamic>(
+      options,
+      &mut Scope::new(),
+      &self.policy.ast,
+      name,
+      args,
+    );
+
+    match result {
+      Ok(value) => Some(value),
+      Err(error) => {
+        self.fail(*error, name);
+        None
+      }
+    }
+  }
+
+  fn fail(&mut self, mut error: EvalAltResult, callback: &str) {
+    // the failing statement, not the call into the script
+    while let EvalAltResult::ErrorInFunctionCall(_, _, inner, _) = error {
+      error = *inner;
+    }
+    let position = error.take_position();
+    let message = format!("in `{callback}`: {error}");
+    self.error = Some(format!("{:#}", located(&self.policy.path, position, message)));
+  }
+}
+
+impl PALTable for ScriptPALTable {
+  fn on_hit(&mut self, access: &Access, frame: usize) {
+    if let Some(entry) = self.entries.get_mut(frame) {
+      entry.last_use = access.time;
+      entry.uses += 1;
+      entry.modified |= access.kind.is_write();
+    }
+    self.call("on_hit", (access_map(access), frame as INT));
+  }
+
+  fn on_fault(&mut self, access: &Access) {
+    self.call("on_fault", (access_map(access),));
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let resident = self
+      .entries
+      .iter()
+      .map(|(frame, entry)| entry.to_map(frame).into())
+      .collect::<Array>();
+
+    if let Some(victim) = self.call("choose_victim", (resident,)) {
+      let type_name = victim.type_name();
+      match victim.as_int() {
+        Ok(frame) if frame >= 0 && self.entries.contains(frame as usize) => {
+          return frame as usize;
+        }
+        Ok(frame) => {
+          self.error = Some(format!(
+            "{}: `choose_victim` returned frame {frame}, which holds no page",
+            self.policy.path
+          ))
+        }
+        Err(_) => {
+          self.error = Some(format!(
+            "{}: `choose_victim` returned a {type_name}, not a frame",
+            self.policy.path
+          ))
+        }
+      }
+    }
+
+    self.entries.front().unwrap()
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    if self.entries.remove(frame).is_some() {
+      self.call("on_evict", (frame as INT,));
+    }
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    self.entries.push_back(
+      frame,
+      ScriptEntry {
+        page: access.page,
+        loaded: access.time,
+        last_use: access.time,
+        uses: 1,
+        modified: access.kind.is_write(),
+      },
+    );
+    self.call("on_load", (access_map(access), frame as INT));
+  }
+
+  fn resident(&self) -> usize {
+    self.entries.len()
+  }
+
+  fn error(&self) -> Option<&str> {
+    self.error.as_deref()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("Script PAL Table ({}) {{", self.policy.path);
+    for (frame, entry) in self.entries.iter() {
+      println!("   [{frame}]: {entry:?}");
+    }
+    println!("   this: {}", self.state);
+    println!("}}");
+  }
+}
@@ -1,60 +1,45 @@
-use std::collections::VecDeque;
-
-use super::PALTable;
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct SecondChancePALTableEntry {
-  pub frame: usize,
-  pub accessed: bool,
-}
+use super::{list::FrameList, Access, PALTable};
 
 #[derive(Debug, Clone)]
 pub struct SecondChancePALTable {
-  pub entries: VecDeque<SecondChancePALTableEntry>,
+  /// Frames in load order and whether they were accessed since they were last skipped
+  pub entries: FrameList<bool>,
 }
 
 impl PALTable for SecondChancePALTable {
-  fn find_frame_to_deallocate(&mut self) -> usize {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if let Some(accessed) = self.entries.get_mut(frame) {
+      *accessed = true;
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
     // iterate circularly through the entries
-    let mut index = 0;
+    let mut cursor = self.entries.front().unwrap();
     loop {
-      let entry = &mut self.entries[index];
-      if entry.accessed {
-        entry.accessed = false;
-        index = (index + 1) % self.entries.len();
-      } else {
-        let removed_frame = self.entries.remove(index).unwrap().frame;
-        return removed_frame;
+      let accessed = self.entries.get_mut(cursor).unwrap();
+      if !*accessed {
+        return cursor;
       }
+      *accessed = false;
+      cursor = self
+        .entries
+        .next(cursor)
+        .or(self.entries.front())
+        .unwrap();
     }
   }
 
-  fn update_access(&mut self, index: usize) {
-    self.entries[index].accessed = true;
+  fn on_evict(&mut self, frame: usize) {
+    self.entries.remove(frame);
   }
 
-  fn insert(&mut self, frame: usize) -> Option<usize> {
-    match self.entries.iter().position(|x| x.frame == frame) {
-      Some(index) => {
-        self.update_access(index);
-        None
-      }
-      None => {
-        let frame_to_deallocate = if self.entries.len() >= self.entries.capacity() {
-          let deallocated_frame = self.find_frame_to_deallocate();
-          Some(deallocated_frame)
-        } else {
-          None
-        };
-
-        self.entries.push_back(SecondChancePALTableEntry {
-          frame,
-          accessed: false,
-        });
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    self.entries.push_back(frame, false);
+  }
 
-        frame_to_deallocate
-      }
-    }
+  fn resident(&self) -> usize {
+    self.entries.len()
   }
 
   fn clone_dyn(&self) -> Box<dyn PALTable> {
@@ -63,7 +48,7 @@ impl PALTable for SecondChancePALTable {
 
   fn print(&self) {
     println!("SecondChance PAL Table {{");
-    for SecondChancePALTableEntry { frame, accessed } in self.entries.iter() {
+    for (frame, accessed) in self.entries.iter() {
       println!("   [{frame}]: {accessed}");
     }
     println!("}}");
@@ -0,0 +1,69 @@
+use super::{list::FrameList, Access, PALTable};
+
+/// SIEVE (Zhang et al.). Frames stay in load order, a reference only sets their visited bit. The
+/// hand walks from the oldest frame to the newest, clearing visited bits until it finds a frame
+/// without one, and keeps its place for the next eviction. Unlike CLOCK, survivors are never
+/// moved, so new frames wait at the newest end until the hand wraps around.
+#[derive(Debug, Clone)]
+pub struct SievePALTable {
+  /// Loaded frames and their visited bit, oldest first
+  pub entries: FrameList<bool>,
+  /// Next frame the hand looks at, `None` to start over from the oldest
+  pub hand: Option<usize>,
+}
+
+impl SievePALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      entries: FrameList::with_capacity(capacity),
+      hand: None,
+    }
+  }
+}
+
+impl PALTable for SievePALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    if let Some(visited) = self.entries.get_mut(frame) {
+      *visited = true;
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let mut frame = self.hand.or(self.entries.front()).unwrap();
+    while let Some(visited) = self.entries.get_mut(frame).filter(|visited| **visited) {
+      *visited = false;
+      frame = self.entries.next(frame).or(self.entries.front()).unwrap();
+    }
+
+    self.hand = self.entries.next(frame);
+    frame
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    if self.hand == Some(frame) {
+      self.hand = self.entries.next(frame);
+    }
+    self.entries.remove(frame);
+  }
+
+  fn on_load(&mut self, _access: &Access, frame: usize) {
+    self.entries.push_back(frame, false);
+  }
+
+  fn resident(&self) -> usize {
+    self.entries.len()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("SIEVE PAL Table {{");
+    for (frame, visited) in self.entries.iter() {
+      let hand = if self.hand == Some(frame) { " <-" } else { "" };
+      println!("   [{frame}]: {visited}{hand}");
+    }
+    println!("}}");
+  }
+}
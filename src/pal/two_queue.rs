@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use super::{list::FrameList, Access, PALTable};
+
+/// 2Q (Johnson and Shasha). Pages seen once wait in the FIFO A1in, and when they leave it their
+/// identity is kept in the ghost queue A1out. Only a page referenced again while in A1out is taken
+/// as hot and goes to Am, managed as LRU, so a scan never pushes hot pages out.
+#[derive(Debug, Clone)]
+pub struct TwoQueuePALTable {
+  /// Resident pages seen once and their frames, in load order
+  pub a1in: FrameList<usize>,
+  /// Pages recently evicted from A1in, oldest first
+  pub a1out: FrameList<()>,
+  /// Resident hot pages and their frames, least recently used first
+  pub am: FrameList<usize>,
+  /// Page held by every frame
+  pub pages: HashMap<usize, usize>,
+  /// A1in is only evicted from while above this size, a quarter of the frames
+  pub kin: usize,
+  /// Size of A1out, half of the frames
+  pub kout: usize,
+}
+
+impl TwoQueuePALTable {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      a1in: FrameList::with_capacity(capacity),
+      a1out: FrameList::with_capacity(capacity),
+      am: FrameList::with_capacity(capacity),
+      pages: HashMap::with_capacity(capacity),
+      kin: (capacity / 4).max(1),
+      kout: (capacity / 2).max(1),
+    }
+  }
+}
+
+impl PALTable for TwoQueuePALTable {
+  fn on_hit(&mut self, _access: &Access, frame: usize) {
+    // pages in A1in are not promoted by correlated references, only Am keeps a recency order
+    if let Some(&page) = self.pages.get(&frame) {
+      self.am.move_to_back(page);
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let queue = match self.a1in.len() > self.kin || self.am.is_empty() {
+      true => &self.a1in,
+      false => &self.am,
+    };
+    *queue.get(queue.front().unwrap()).unwrap()
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    let Some(page) = self.pages.remove(&frame) else {
+      return;
+    };
+
+    if self.a1in.remove(page).is_some() {
+      self.a1out.push_back(page, ());
+      if self.a1out.len() > self.kout {
+        self.a1out.pop_front();
+      }
+    } else {
+      self.am.remove(page);
+    }
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    let page = access.page;
+    match self.a1out.remove(page) {
+      Some(()) => self.am.push_back(page, frame),
+      None => self.a1in.push_back(page, frame),
+    }
+    self.pages.insert(frame, page);
+  }
+
+  fn resident(&self) -> usize {
+    self.pages.len()
+  }
+
+  fn parameters(&self) -> Vec<(&'static str, usize)> {
+    vec![
+      ("a1in", self.a1in.len()),
+      ("a1out", self.a1out.len()),
+      ("am", self.am.len()),
+    ]
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("2Q PAL Table {{");
+    println!("   A1in: {:?}", self.a1in.iter().map(|(page, _)| page).collect::<Vec<_>>());
+    println!("   A1out: {:?}", self.a1out.iter().map(|(page, _)| page).collect::<Vec<_>>());
+    println!("   Am: {:?}", self.am.iter().map(|(page, _)| page).collect::<Vec<_>>());
+    println!("}}");
+  }
+}
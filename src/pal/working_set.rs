@@ -0,0 +1,71 @@
+use super::{list::FrameList, Access, PALTable};
+
+/// Working set: a page stays resident while it was referenced within the last `tau` references,
+/// and its frame is released as soon as it falls out of that window, so the allocation follows
+/// the working set instead of being fixed. When the working set outgrows memory, the least
+/// recently used page goes.
+#[derive(Debug, Clone)]
+pub struct WorkingSetPALTable {
+  /// Frames and the tick of their last access, least recently used first
+  pub entries: FrameList<u64>,
+  /// Window of the working set, in references
+  pub tau: u64,
+  /// Tick of the latest reference
+  pub now: u64,
+}
+
+impl WorkingSetPALTable {
+  pub fn new(capacity: usize, tau: u64) -> Self {
+    Self {
+      entries: FrameList::with_capacity(capacity),
+      tau,
+      now: 0,
+    }
+  }
+}
+
+impl PALTable for WorkingSetPALTable {
+  fn on_hit(&mut self, access: &Access, frame: usize) {
+    self.now = access.time;
+    if let Some(last_access) = self.entries.get_mut(frame) {
+      *last_access = access.time;
+      self.entries.move_to_back(frame);
+    }
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    self.entries.front().unwrap()
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    self.entries.remove(frame);
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    self.now = access.time;
+    self.entries.push_back(frame, access.time);
+  }
+
+  fn resident(&self) -> usize {
+    self.entries.len()
+  }
+
+  fn release(&mut self) -> Option<usize> {
+    let frame = self.entries.front()?;
+    let last_access = *self.entries.get(frame)?;
+
+    (self.now - last_access >= self.tau).then_some(frame)
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("WorkingSet PAL Table {{");
+    for (frame, last_access) in self.entries.iter() {
+      println!("   [{frame}]: {last_access}");
+    }
+    println!("}}");
+  }
+}
@@ -0,0 +1,134 @@
+use super::{list::FrameRing, Access, PALTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WSClockEntry {
+  pub referenced: bool,
+  pub modified: bool,
+  /// Tick the hand last saw the frame referenced at, or its load
+  pub last_use: u64,
+}
+
+/// WSClock: a clock whose hand looks for a clean page outside the working set window `tau`.
+/// Referenced frames get their time of last use refreshed, and old dirty frames have their write
+/// back scheduled: the MMU writes them to swap through `write_back`, so they are clean by the
+/// time the hand comes back. When a whole turn finds nothing, the first clean frame goes, or
+/// failing that the frame under the hand.
+#[derive(Debug, Clone)]
+pub struct WSClockPALTable {
+  pub ring: FrameRing<WSClockEntry>,
+  /// Window of the working set, in references
+  pub tau: u64,
+  /// Tick of the latest reference
+  pub now: u64,
+  /// Frames whose write back was scheduled and not yet handed to the MMU
+  pub write_backs: Vec<usize>,
+}
+
+impl WSClockPALTable {
+  pub fn new(capacity: usize, tau: u64) -> Self {
+    Self {
+      ring: FrameRing::with_capacity(capacity),
+      tau,
+      now: 0,
+      write_backs: Vec::new(),
+    }
+  }
+
+  fn take(&mut self, slot: usize) -> usize {
+    self.ring.hand = slot;
+    self.ring.slot(slot).unwrap().0
+  }
+}
+
+impl PALTable for WSClockPALTable {
+  fn on_hit(&mut self, access: &Access, frame: usize) {
+    self.now = access.time;
+    if let Some(entry) = self.ring.get_mut(frame) {
+      entry.referenced = true;
+      entry.modified |= access.kind.is_write();
+    }
+  }
+
+  fn on_fault(&mut self, access: &Access) {
+    self.now = access.time;
+  }
+
+  fn choose_victim(&mut self) -> usize {
+    let now = self.now;
+    let slots = self.ring.slots();
+    let mut scheduled = false;
+
+    // first turn, and a second one if it scheduled write backs
+    for step in 0..2 * slots {
+      if step == slots && !scheduled {
+        break;
+      }
+
+      let slot = self.ring.ahead(step);
+      let Some((frame, entry)) = self.ring.slot_mut(slot) else {
+        continue;
+      };
+
+      if entry.referenced {
+        entry.referenced = false;
+        entry.last_use = now;
+      } else if now - entry.last_use >= self.tau {
+        if !entry.modified {
+          return self.take(slot);
+        }
+        entry.modified = false;
+        self.write_backs.push(frame);
+        scheduled = true;
+      }
+    }
+
+    let clean = (0..slots)
+      .map(|step| self.ring.ahead(step))
+      .find(|&slot| matches!(self.ring.slot(slot), Some((_, entry)) if !entry.modified));
+    let hand = (0..slots)
+      .map(|step| self.ring.ahead(step))
+      .find(|&slot| self.ring.slot(slot).is_some())
+      .unwrap();
+    self.take(clean.unwrap_or(hand))
+  }
+
+  fn on_evict(&mut self, frame: usize) {
+    self.ring.remove(frame);
+  }
+
+  fn on_load(&mut self, access: &Access, frame: usize) {
+    let entry = WSClockEntry {
+      referenced: false,
+      modified: access.kind.is_write(),
+      last_use: access.time,
+    };
+    self.ring.insert(frame, entry);
+  }
+
+  fn resident(&self) -> usize {
+    self.ring.len()
+  }
+
+  fn write_back(&mut self) -> Option<usize> {
+    self.write_backs.pop()
+  }
+
+  fn clone_dyn(&self) -> Box<dyn PALTable> {
+    Box::new(self.clone())
+  }
+
+  fn print(&self) {
+    println!("WSClock PAL Table {{");
+    for slot in 0..self.ring.slots() {
+      let hand = if slot == self.ring.hand { " <-" } else { "" };
+      match self.ring.slot(slot) {
+        Some((frame, entry)) => println!(
+          "   [{frame}]: ({}, {}) {}{hand}",
+          entry.referenced as u8, entry.modified as u8, entry.last_use
+        ),
+        None => println!("   [ ]{hand}"),
+      }
+    }
+    println!("}}");
+  }
+}
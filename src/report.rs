@@ -0,0 +1,386 @@
+use serde::Serialize;
+
+use crate::cli::Output;
+
+/// Outcome of a simulation run together with the parameters that produced it
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+  pub algorithm: String,
+  pub trace: String,
+  pub page_size: usize,
+  pub frames: usize,
+  pub references: usize,
+  pub hits: usize,
+  pub misses: usize,
+  pub evictions: usize,
+  /// Evictions writing a modified page back, when the run tracked them
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub dirty_evictions: Option<usize>,
+  pub fault_rate: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub seeds: Option<SeedsReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_table: Option<PageTableReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub swap: Option<SwapReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub working_set: Option<WorkingSetReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tlb: Option<TLBReport>,
+}
+
+/// Misses over runs with consecutive seeds, the other values are the ones of the first run
+#[derive(Debug, Clone, Serialize)]
+pub struct SeedsReport {
+  /// Seed of the first run
+  pub seed: u64,
+  /// Runs made, a single one for the algorithms the seed does not change
+  pub runs: usize,
+  pub misses_mean: f64,
+  /// Sample standard deviation
+  pub misses_stddev: f64,
+}
+
+impl SeedsReport {
+  pub fn new(seed: u64, misses: &[usize]) -> Self {
+    let runs = misses.len();
+    let mean = misses.iter().sum::<usize>() as f64 / runs as f64;
+    let variance = match runs {
+      0 | 1 => 0.0,
+      _ => {
+        misses
+          .iter()
+          .map(|&misses| (misses as f64 - mean).powi(2))
+          .sum::<f64>()
+          / (runs - 1) as f64
+      }
+    };
+
+    Self {
+      seed,
+      runs,
+      misses_mean: mean,
+      misses_stddev: variance.sqrt(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageTableReport {
+  pub layout: String,
+  pub levels: Vec<usize>,
+  /// Memory taken by the page table, in pages
+  pub table_pages: usize,
+  pub walks: usize,
+  /// Tables visited per walk, on average. For an inverted table, the anchor and chain entries
+  /// probed per lookup.
+  pub walk_depth: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub chains: Option<ChainReport>,
+}
+
+/// Hash chains of an inverted page table at the end of the run
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainReport {
+  /// Non empty chains
+  pub chains: usize,
+  pub max_length: usize,
+  pub mean_length: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapReport {
+  /// Faults served by a zeroed frame, first touches included
+  pub zero_fills: usize,
+  /// Faults reading the page back from swap
+  pub page_ins: usize,
+  pub page_outs: usize,
+  /// Swap slots allocated
+  pub slots: usize,
+  /// Slot allocations served by a slot released earlier
+  pub slot_reuses: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkingSetReport {
+  pub tau: usize,
+  pub mean_size: f64,
+  pub max_size: usize,
+  /// Frames in use, on average. Below the frame count when the policy releases frames
+  pub mean_resident: f64,
+  /// Frames the policy gave back on its own
+  pub released: usize,
+  /// Working set size every `tau` references
+  pub sizes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TLBReport {
+  pub entries: usize,
+  pub ways: usize,
+  pub algorithm: String,
+  pub hits: usize,
+  pub misses: usize,
+  pub hit_rate: f64,
+}
+
+impl Report {
+  pub fn render(&self, output: &Output) -> anyhow::Result<String> {
+    Ok(match output {
+      Output::Json => serde_json::to_string_pretty(self)?,
+      _ => render_all(std::slice::from_ref(self), output)?,
+    })
+  }
+
+  /// Every reported value by name, optional sections only when they are present
+  pub fn fields(&self) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+      ("algorithm", self.algorithm.clone()),
+      ("trace", self.trace.clone()),
+      ("page_size", self.page_size.to_string()),
+      ("frames", self.frames.to_string()),
+      ("references", self.references.to_string()),
+      ("misses", self.misses.to_string()),
+      ("hits", self.hits.to_string()),
+      ("evictions", self.evictions.to_string()),
+    ];
+
+    if let Some(dirty) = self.dirty_evictions {
+      fields.extend([
+        ("clean_evictions", (self.evictions - dirty).to_string()),
+        ("dirty_evictions", dirty.to_string()),
+      ]);
+    }
+
+    fields.push(("fault_rate", format!("{:.4}", self.fault_rate)));
+
+    if let Some(seeds) = &self.seeds {
+      fields.extend([
+        ("seed", seeds.seed.to_string()),
+        ("runs", seeds.runs.to_string()),
+        ("misses_mean", format!("{:.4}", seeds.misses_mean)),
+        ("misses_stddev", format!("{:.4}", seeds.misses_stddev)),
+      ]);
+    }
+
+    if let Some(page_table) = &self.page_table {
+      fields.extend([
+        ("page_table", page_table.layout.clone()),
+        (
+          "page_table_levels",
+          page_table
+            .levels
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        ),
+        ("table_pages", page_table.table_pages.to_string()),
+        ("walks", page_table.walks.to_string()),
+        ("walk_depth", format!("{:.4}", page_table.walk_depth)),
+      ]);
+
+      if let Some(chains) = &page_table.chains {
+        fields.extend([
+          ("chains", chains.chains.to_string()),
+          ("max_chain_length", chains.max_length.to_string()),
+          ("mean_chain_length", format!("{:.4}", chains.mean_length)),
+        ]);
+      }
+    }
+
+    if let Some(swap) = &self.swap {
+      fields.extend([
+        ("zero_fills", swap.zero_fills.to_string()),
+        ("page_ins", swap.page_ins.to_string()),
+        ("page_outs", swap.page_outs.to_string()),
+        ("swap_slots", swap.slots.to_string()),
+        ("slot_reuses", swap.slot_reuses.to_string()),
+      ]);
+    }
+
+    if let Some(working_set) = &self.working_set {
+      fields.extend([
+        ("tau", working_set.tau.to_string()),
+        ("working_set_mean", format!("{:.4}", working_set.mean_size)),
+        ("working_set_max", working_set.max_size.to_string()),
+        ("mean_resident", format!("{:.4}", working_set.mean_resident)),
+        ("released", working_set.released.to_string()),
+      ]);
+    }
+
+    if let Some(tlb) = &self.tlb {
+      fields.extend([
+        ("tlb_entries", tlb.entries.to_string()),
+        ("tlb_ways", tlb.ways.to_string()),
+        ("tlb_algorithm", tlb.algorithm.clone()),
+        ("tlb_hits", tlb.hits.to_string()),
+        ("tlb_misses", tlb.misses.to_string()),
+        ("tlb_hit_rate", format!("{:.4}", tlb.hit_rate)),
+      ]);
+    }
+
+    fields
+  }
+
+  fn text(&self) -> String {
+    self
+      .fields()
+      .into_iter()
+      .map(|(name, value)| format!("{}: {value}", label(name)))
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+
+  pub fn header(&self) -> Vec<String> {
+    self
+      .fields()
+      .into_iter()
+      .map(|(name, _)| name.to_string())
+      .collect()
+  }
+
+  pub fn row(&self) -> Vec<String> {
+    self
+      .fields()
+      .into_iter()
+      .map(|(_, value)| value)
+      .collect()
+  }
+
+  /// Values of the `header` columns, empty for the sections this report does not have
+  pub fn cells(&self, header: &[String]) -> Vec<String> {
+    let fields = self.fields();
+    header
+      .iter()
+      .map(|title| {
+        fields
+          .iter()
+          .find(|(name, _)| name == title)
+          .map(|(_, value)| value.clone())
+          .unwrap_or_default()
+      })
+      .collect()
+  }
+}
+
+/// `page_size` as `Page size`, `tlb_hits` as `TLB hits`
+fn label(name: &str) -> String {
+  let label = name
+    .split('_')
+    .map(|word| match word {
+      "tlb" => "TLB".to_string(),
+      word => word.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join(" ");
+
+  let mut chars = label.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => label,
+  }
+}
+
+/// Renders several reports at once: a JSON array, one CSV or table row per report, or the text
+/// blocks separated by blank lines
+pub fn render_all(reports: &[Report], output: &Output) -> anyhow::Result<String> {
+  Ok(match output {
+    Output::Text => reports
+      .iter()
+      .map(Report::text)
+      .collect::<Vec<_>>()
+      .join("\n\n"),
+    Output::Json => serde_json::to_string_pretty(reports)?,
+    Output::Table => {
+      let header = header(reports);
+      table(
+        &header
+          .iter()
+          .map(|title| title.replace('_', " "))
+          .collect::<Vec<_>>(),
+        &reports.iter().map(|report| report.cells(&header)).collect::<Vec<_>>(),
+      )
+    }
+    Output::Csv => {
+      let header = header(reports);
+      csv(
+        &header,
+        &reports.iter().map(|report| report.cells(&header)).collect::<Vec<_>>(),
+      )
+    }
+  })
+}
+
+/// Columns of every report, in field order. Optional sections depend on the algorithm, so the
+/// reports of a sweep do not all have the same ones
+fn header(reports: &[Report]) -> Vec<String> {
+  let mut header: Vec<String> = Vec::new();
+  for report in reports {
+    let mut position = 0;
+    for name in report.header() {
+      match header.iter().position(|title| *title == name) {
+        Some(index) => position = index + 1,
+        None => {
+          header.insert(position, name);
+          position += 1;
+        }
+      }
+    }
+  }
+  header
+}
+
+pub fn csv(header: &[String], rows: &[Vec<String>]) -> String {
+  let line = |cells: &[String]| {
+    cells
+      .iter()
+      .map(|cell| match cell.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", cell.replace('"', "\"\"")),
+        false => cell.clone(),
+      })
+      .collect::<Vec<_>>()
+      .join(",")
+  };
+
+  std::iter::once(line(header))
+    .chain(rows.iter().map(|row| line(row)))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Renders rows as a bordered table, with every column as wide as its widest cell
+pub fn table(header: &[String], rows: &[Vec<String>]) -> String {
+  let widths = header
+    .iter()
+    .enumerate()
+    .map(|(column, title)| {
+      rows
+        .iter()
+        .map(|row| row[column].len())
+        .fold(title.len(), usize::max)
+    })
+    .collect::<Vec<_>>();
+
+  let separator = widths
+    .iter()
+    .map(|width| "-".repeat(width + 2))
+    .collect::<Vec<_>>()
+    .join("+");
+  let separator = format!("+{separator}+");
+
+  let line = |cells: &[String]| {
+    let cells = cells
+      .iter()
+      .zip(widths.iter())
+      .map(|(cell, width)| format!(" {cell:<width$} "))
+      .collect::<Vec<_>>()
+      .join("|");
+    format!("|{cells}|")
+  };
+
+  let mut lines = vec![separator.clone(), line(header), separator.clone()];
+  lines.extend(rows.iter().map(|row| line(row)));
+  lines.push(separator);
+  lines.join("\n")
+}
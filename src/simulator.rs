@@ -0,0 +1,518 @@
+use std::str::FromStr;
+
+use crate::{
+  analysis::working_set::WorkingSet,
+  cli::{pal::PALOptions, translate::TranslateOptions},
+  memory::{
+    primary::PrimaryMemory,
+    secondary::{FaultKind, SecondaryMemory},
+  },
+  mmu::{address::LogicalAddress, tlb::TLB, trace::Trace, TranslationResult, MMU},
+  pal::{PALAlgorithm, PAL},
+  report::{ChainReport, PageTableReport, Report, SwapReport, TLBReport, WorkingSetReport},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Statistics {
+  pub hits: usize,
+  pub misses: usize,
+  pub evictions: usize,
+}
+
+impl Statistics {
+  pub fn record(&mut self, result: &TranslationResult) {
+    match result {
+      TranslationResult::Hit => self.hits += 1,
+      TranslationResult::Fault { evicted, .. } => {
+        self.misses += 1;
+        if evicted.is_some() {
+          self.evictions += 1;
+        }
+      }
+    }
+  }
+
+  pub fn references(&self) -> usize {
+    self.hits + self.misses
+  }
+
+  pub fn fault_rate(&self) -> f64 {
+    match self.references() {
+      0 => 0.0,
+      references => self.misses as f64 / references as f64,
+    }
+  }
+}
+
+/// A whole simulated system: the MMU with its page table, the PAL and the primary and secondary
+/// memories they share. Simulators own all of their state, so several of them can run side by side.
+#[derive(Debug)]
+pub struct Simulator {
+  pub algorithm: PALAlgorithm,
+  pub mmu: MMU,
+  pub pal: PAL,
+  pub memory: PrimaryMemory,
+  pub swap: SecondaryMemory,
+  pub statistics: Statistics,
+  /// Working set of the trace, followed for the policies built on it
+  pub working_set: Option<WorkingSet>,
+  /// Resident pages summed over every reference
+  pub resident: usize,
+}
+
+impl Simulator {
+  pub fn new(algorithm: PALAlgorithm, frame_count: usize, page_size: usize) -> Self {
+    Self::with_options(algorithm, frame_count, page_size, &PALOptions::default())
+  }
+
+  pub fn with_options(
+    algorithm: PALAlgorithm,
+    frame_count: usize,
+    page_size: usize,
+    options: &PALOptions,
+  ) -> Self {
+    Self::with_mmu(algorithm, frame_count, MMU::new(page_size), options)
+  }
+
+  /// Same as `with_options`, around an MMU set up with another page table or a TLB
+  pub fn with_mmu(
+    algorithm: PALAlgorithm,
+    frame_count: usize,
+    mmu: MMU,
+    options: &PALOptions,
+  ) -> Self {
+    Self {
+      algorithm,
+      mmu,
+      pal: PAL::with_options(algorithm, frame_count, options),
+      memory: PrimaryMemory::new(frame_count),
+      swap: SecondaryMemory::new(),
+      statistics: Statistics::default(),
+      working_set: matches!(algorithm, PALAlgorithm::WorkingSet | PALAlgorithm::WSClock)
+        .then(|| WorkingSet::new(options.tau as usize)),
+      resident: 0,
+    }
+  }
+
+  pub fn translate(&mut self, address: &LogicalAddress) -> TranslationResult {
+    let result = self
+      .mmu
+      .translate(address, &mut self.memory, &mut self.swap, &mut self.pal);
+    self.statistics.record(&result);
+    if let Some(working_set) = self.working_set.as_mut() {
+      working_set.reference(address.page(self.mmu.page_size));
+    }
+    // frames still holding a page once the PAL released what it wanted to
+    self.resident += self.pal.resident();
+    result
+  }
+
+  pub fn translate_str(&mut self, address: &str) -> anyhow::Result<TranslationResult> {
+    let address = LogicalAddress::from_str(address)?;
+    self.mmu.page_table.check(address.page(self.mmu.page_size))?;
+    Ok(self.translate(&address))
+  }
+
+  pub fn run(&mut self, trace: &Trace) -> Vec<TranslationResult> {
+    self.pal.set_lookahead(trace.next_uses(self.mmu.page_size));
+
+    trace
+      .iter()
+      .map_while(|address| self.step(address))
+      .collect()
+  }
+
+  /// Translates `address`, unless the PAL already failed: the run stops at its first error
+  fn step(&mut self, address: &LogicalAddress) -> Option<TranslationResult> {
+    match self.pal.error() {
+      Some(_) => None,
+      None => Some(self.translate(address)),
+    }
+  }
+
+  /// Runs the trace logging every reference: its outcome and the adaptive state of the PAL
+  pub fn run_verbose(&mut self, trace: &Trace) -> Vec<String> {
+    self.pal.set_lookahead(trace.next_uses(self.mmu.page_size));
+
+    trace
+      .iter()
+      .map_while(|address| {
+        let mut line = format!("{:x} ", address.value);
+        line += &match self.step(address)? {
+          TranslationResult::Hit => "hit".to_string(),
+          TranslationResult::Fault { kind, evicted } => {
+            let mut outcome = match kind {
+              FaultKind::ZeroFill => "fault, zero fill".to_string(),
+              FaultKind::PageIn => "fault, page in".to_string(),
+            };
+            if let Some(frame) = evicted {
+              outcome += &format!(", evicted frame {frame}");
+            }
+            outcome
+          }
+        };
+        for (name, value) in self.pal.parameters() {
+          line += &format!(" {name}={value}");
+        }
+        Some(line)
+      })
+      .collect()
+  }
+
+  pub fn report(&self, trace: &str) -> Report {
+    Report {
+      algorithm: self.algorithm.to_string(),
+      trace: trace.to_string(),
+      page_size: self.mmu.page_size,
+      frames: self.memory.frames.len(),
+      references: self.statistics.references(),
+      hits: self.statistics.hits,
+      misses: self.statistics.misses,
+      evictions: self.statistics.evictions,
+      dirty_evictions: Some(self.mmu.dirty_evictions),
+      fault_rate: self.statistics.fault_rate(),
+      seeds: None,
+      page_table: Some(PageTableReport {
+        layout: self.mmu.page_table.layout().to_string(),
+        levels: self.mmu.page_table.levels(),
+        table_pages: self.mmu.page_table.table_pages(),
+        walks: self.mmu.walks,
+        walk_depth: match self.mmu.walks {
+          0 => 0.0,
+          walks => self.mmu.walk_depth as f64 / walks as f64,
+        },
+        chains: self.mmu.page_table.chains().map(|chains| ChainReport {
+          chains: chains.len(),
+          max_length: chains.iter().copied().max().unwrap_or(0),
+          mean_length: match chains.len() {
+            0 => 0.0,
+            count => chains.iter().sum::<usize>() as f64 / count as f64,
+          },
+        }),
+      }),
+      swap: Some(SwapReport {
+        zero_fills: self.swap.zero_fills,
+        page_ins: self.swap.page_ins,
+        page_outs: self.swap.page_outs,
+        slots: self.swap.allocated,
+        slot_reuses: self.swap.reused,
+      }),
+      working_set: self.working_set.as_ref().map(|working_set| WorkingSetReport {
+        tau: working_set.tau,
+        mean_size: working_set.mean(),
+        max_size: working_set.max,
+        mean_resident: match self.statistics.references() {
+          0 => 0.0,
+          references => self.resident as f64 / references as f64,
+        },
+        released: self.mmu.released,
+        sizes: working_set.samples.clone(),
+      }),
+      tlb: self.mmu.tlb.as_ref().map(|tlb| TLBReport {
+        entries: tlb.entries(),
+        ways: tlb.ways(),
+        algorithm: tlb.algorithm.to_string(),
+        hits: tlb.hits,
+        misses: tlb.misses,
+        hit_rate: match tlb.hits + tlb.misses {
+          0 => 0.0,
+          lookups => tlb.hits as f64 / lookups as f64,
+        },
+      }),
+    }
+  }
+}
+
+pub fn entrypoint(
+  TranslateOptions {
+    output,
+    trace: trace_file,
+    page_table_size,
+    algorithm,
+    pal_table_entries,
+    page_table,
+    address_bits,
+    level_bits,
+    tlb_entries,
+    tlb_ways,
+    tlb_algorithm,
+    pal,
+    verbose,
+  }: &TranslateOptions,
+) -> anyhow::Result<String> {
+  pal.check(&[*algorithm])?;
+  let mut mmu = MMU::with_page_table(
+    page_table.build(
+      *address_bits,
+      *page_table_size,
+      level_bits,
+      *pal_table_entries,
+    )?,
+    *page_table_size,
+  );
+  if *tlb_entries > 0 {
+    let ways = tlb_ways.unwrap_or(*tlb_entries);
+    mmu.tlb = Some(TLB::new(*tlb_entries, ways, *tlb_algorithm)?);
+  }
+  let mut simulator = Simulator::with_mmu(*algorithm, *pal_table_entries, mmu, pal);
+  let trace = Trace::from_file(trace_file)?;
+  simulator.mmu.check(trace_file, &trace)?;
+  match verbose {
+    true => simulator
+      .run_verbose(&trace)
+      .iter()
+      .for_each(|line| eprintln!("{line}")),
+    false => {
+      simulator.run(&trace);
+    }
+  }
+  if let Some(error) = simulator.pal.error() {
+    anyhow::bail!("{error}");
+  }
+
+  simulator.report(trace_file).render(output)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+
+  use crate::pal::script::ScriptPolicy;
+
+  use super::*;
+
+  #[test]
+  fn test_mmu() {
+    let simulator = Simulator::new(PALAlgorithm::LRU, 4096, 4096);
+
+    assert_eq!(simulator.mmu.page_size, 4096);
+    assert_eq!(simulator.mmu.page_table.pages(), 1 << (32 - 4096u32.ilog2()));
+  }
+
+  #[test]
+  fn test_translate() {
+    let mut simulator = Simulator::new(PALAlgorithm::LRU, 4096, 4096);
+
+    assert_eq!(
+      simulator.translate_str("345678").unwrap(),
+      TranslationResult::Fault {
+        kind: FaultKind::ZeroFill,
+        evicted: None
+      }
+    );
+    assert_eq!(
+      simulator.translate_str("345678").unwrap(),
+      TranslationResult::Hit
+    );
+    // past the 32 bits of the default table
+    assert!(simulator.translate_str("100000000").is_err());
+  }
+
+  #[test]
+  fn manually() {
+    let mut simulator = Simulator::new(PALAlgorithm::LRU, 4096, 4096);
+
+    ["001123", "002123", "001123", "003123", "005123", "006123"]
+      .iter()
+      .for_each(|address| {
+        simulator.translate_str(address).unwrap();
+      });
+  }
+
+  #[test]
+  fn complex() {
+    let mut simulator = Simulator::new(PALAlgorithm::LRU, 4096, 4096);
+
+    // Generate random addresses
+    let addresses = (0..10000)
+      .map(|_| {
+        let mut rng = rand::thread_rng();
+        let address: u32 = rng.gen();
+        format!("{:06x}", address)
+      })
+      .collect::<Vec<String>>();
+
+    addresses.iter().for_each(|address| {
+      simulator.translate_str(address).unwrap();
+    });
+  }
+
+  #[test]
+  fn side_by_side() {
+    let addresses = (0..10000)
+      .map(|i| format!("{:06x}", (i * 7919 % 64) << 12))
+      .collect::<Vec<String>>();
+
+    let faults = |algorithm, frames| {
+      let mut simulator = Simulator::new(algorithm, frames, 4096);
+      addresses.iter().for_each(|address| {
+        simulator.translate_str(address).unwrap();
+      });
+      simulator.statistics
+    };
+
+    let sequential = [(PALAlgorithm::LRU, 16), (PALAlgorithm::Fifo, 32)]
+      .map(|(algorithm, frames)| faults(algorithm, frames));
+
+    let parallel = std::thread::scope(|scope| {
+      [(PALAlgorithm::LRU, 16), (PALAlgorithm::Fifo, 32)]
+        .map(|(algorithm, frames)| scope.spawn(move || faults(algorithm, frames)))
+        .map(|handle| handle.join().unwrap())
+    });
+
+    assert_eq!(sequential, parallel);
+  }
+
+  #[test]
+  fn report() {
+    let mut simulator = Simulator::new(PALAlgorithm::SecondChance, 2, 4096);
+
+    ["001123", "002123", "001123", "003123", "005123", "003123"]
+      .iter()
+      .for_each(|address| {
+        simulator.translate_str(address).unwrap();
+      });
+
+    let report = simulator.report("manual");
+    assert_eq!(report.algorithm, "second-chance");
+    assert_eq!(report.references, 6);
+    assert_eq!(report.hits, 2);
+    assert_eq!(report.misses, 4);
+    assert_eq!(report.evictions, 2);
+    assert_eq!(report.fault_rate, 4.0 / 6.0);
+
+    let json: serde_json::Value =
+      serde_json::from_str(&report.render(&crate::cli::Output::Json).unwrap()).unwrap();
+    assert_eq!(json["misses"], 4);
+    assert_eq!(json["frames"], 2);
+  }
+
+  #[test]
+  fn tlb_follows_evictions() {
+    let mut simulator = Simulator::new(PALAlgorithm::Fifo, 2, 4096);
+    simulator.mmu.tlb = Some(TLB::new(4, 4, PALAlgorithm::LRU).unwrap());
+
+    // 3 evicts 1 from memory, so 1 must not be served by the TLB anymore
+    ["001000", "002000", "001000", "003000", "001000", "003000"]
+      .iter()
+      .for_each(|address| {
+        simulator.translate_str(address).unwrap();
+      });
+
+    let report = simulator.report("manual");
+    assert_eq!((report.misses, report.hits), (4, 2));
+
+    let tlb = report.tlb.unwrap();
+    assert_eq!((tlb.hits, tlb.misses), (2, 4));
+  }
+
+  #[test]
+  fn swap() {
+    let mut simulator = Simulator::new(PALAlgorithm::Fifo, 2, 4096);
+
+    // 1, 2 and 3 are first touches, then every fault reads back a page evicted dirty
+    let kinds = ["W 1000", "W 2000", "W 3000", "W 1000", "W 2000", "W 3000"]
+      .iter()
+      .map(|address| match simulator.translate_str(address).unwrap() {
+        TranslationResult::Fault { kind, .. } => kind,
+        TranslationResult::Hit => panic!("{address} hit"),
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(kinds[..3], [FaultKind::ZeroFill; 3]);
+    assert_eq!(kinds[3..], [FaultKind::PageIn; 3]);
+
+    let swap = simulator.report("manual").swap.unwrap();
+    assert_eq!((swap.zero_fills, swap.page_ins, swap.page_outs), (3, 3, 4));
+    // pages read back are dirtied again, so their slots are recycled
+    assert_eq!((swap.slots, swap.slot_reuses), (2, 2));
+  }
+
+  #[test]
+  fn working_set() {
+    let options = PALOptions {
+      tau: 4,
+      ..PALOptions::default()
+    };
+    let mut simulator = Simulator::with_options(PALAlgorithm::WorkingSet, 8, 4096, &options);
+
+    // 1 and 2 leave the window while 3 and 4 loop, their frames are given back
+    ["1000", "2000", "3000", "4000", "3000", "4000", "3000", "4000", "1000"]
+      .iter()
+      .for_each(|address| {
+        simulator.translate_str(address).unwrap();
+      });
+
+    assert_eq!(simulator.mmu.released, 2);
+    assert_eq!(simulator.statistics.misses, 5);
+    // 1 comes back in the lowest frame released
+    assert_eq!(simulator.mmu.page_table.get_frame(1), Some(0));
+
+    let working_set = simulator.report("manual").working_set.unwrap();
+    assert_eq!((working_set.max_size, working_set.sizes), (4, vec![4, 2]));
+    // resident: 1, 2, 3, 4, 3, 2, 2, 2, 3
+    assert_eq!(working_set.mean_resident, 22.0 / 9.0);
+  }
+
+  #[test]
+  fn wsclock_write_back() {
+    let options = PALOptions {
+      tau: 2,
+      ..PALOptions::default()
+    };
+    let mut simulator = Simulator::with_options(PALAlgorithm::WSClock, 3, 4096, &options);
+
+    // 4 schedules the write back of 1 and takes the frame of 2, 5 then takes the cleaned 1
+    ["W 1000", "R 2000", "R 3000", "R 4000", "R 3000", "R 5000"]
+      .iter()
+      .for_each(|address| {
+        simulator.translate_str(address).unwrap();
+      });
+
+    assert_eq!(simulator.mmu.page_table.get_frame(1), None);
+    assert_eq!(simulator.mmu.dirty_evictions, 0);
+    assert_eq!(simulator.report("manual").swap.unwrap().page_outs, 1);
+  }
+
+  #[test]
+  fn script_error_stops_the_run() {
+    let options = PALOptions {
+      policy_file: Some(
+        ScriptPolicy::compile("policy.rhai", "fn choose_victim(resident) { 7 }").unwrap(),
+      ),
+      ..PALOptions::default()
+    };
+    let mut trace = Trace::new();
+    ["1000", "2000", "3000", "4000", "5000"]
+      .iter()
+      .for_each(|address| trace.add(LogicalAddress::from_str(address).unwrap()));
+
+    // the first victim is a frame holding no page, nothing runs after that reference
+    let mut simulator = Simulator::with_options(PALAlgorithm::Script, 2, 4096, &options);
+    assert_eq!(simulator.run(&trace).len(), 3);
+    assert_eq!(simulator.statistics.references(), 3);
+    assert!(simulator.pal.error().is_some());
+
+    let mut simulator = Simulator::with_options(PALAlgorithm::Script, 2, 4096, &options);
+    assert_eq!(simulator.run_verbose(&trace).len(), 3);
+  }
+
+  #[test]
+  fn verbose() {
+    let mut trace = Trace::new();
+    ["W 1000", "2000", "1000", "3000", "1000"]
+      .iter()
+      .for_each(|address| trace.add(LogicalAddress::from_str(address).unwrap()));
+
+    let mut simulator = Simulator::new(PALAlgorithm::Fifo, 2, 4096);
+    assert_eq!(
+      simulator.run_verbose(&trace),
+      [
+        "1000 fault, zero fill",
+        "2000 fault, zero fill",
+        "1000 hit",
+        "3000 fault, zero fill, evicted frame 0",
+        "1000 fault, page in, evicted frame 1",
+      ]
+    );
+  }
+}
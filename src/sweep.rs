@@ -0,0 +1,299 @@
+use std::{
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+  },
+  thread,
+};
+
+use crate::{
+  cli::{pal::PALOptions, sweep::SweepOptions},
+  mmu::{trace::Trace, MMU},
+  pal::PALAlgorithm,
+  report::{self, Report, SeedsReport},
+  simulator::Simulator,
+};
+
+/// A single simulation of the sweep
+#[derive(Debug, Clone, Copy)]
+struct Job {
+  trace: usize,
+  algorithm: PALAlgorithm,
+  frames: usize,
+}
+
+/// Simulates every combination of traces, algorithms and frame counts, spreading the simulations
+/// over `jobs` threads. Reports come back ordered by trace, then algorithm, then frame count.
+///
+/// Seeded algorithms run `seeds` times, with the seeds following `options.seed`. With more than
+/// one, every report gets the mean and standard deviation of the misses over its runs.
+///
+/// Fails for a trace page the page table cannot map, before anything runs, or with the first
+/// error a policy ran into, a script failing at run time.
+pub fn sweep(
+  traces: &[(String, Trace)],
+  algorithms: &[PALAlgorithm],
+  frames: &[usize],
+  page_size: usize,
+  options: &PALOptions,
+  seeds: usize,
+  jobs: usize,
+) -> anyhow::Result<Vec<Report>> {
+  // every simulation runs on the same default table
+  let mmu = MMU::new(page_size);
+  for (name, trace) in traces {
+    mmu.check(name, trace)?;
+  }
+
+  let simulations = (0..traces.len())
+    .flat_map(|trace| {
+      algorithms.iter().flat_map(move |&algorithm| {
+        frames.iter().map(move |&frames| Job {
+          trace,
+          algorithm,
+          frames,
+        })
+      })
+    })
+    .collect::<Vec<_>>();
+  let runs = |job: &Job| match job.algorithm.is_seeded() {
+    true => seeds.max(1),
+    false => 1,
+  };
+  let queue = simulations
+    .iter()
+    .flat_map(|job| (0..runs(job) as u64).map(move |run| (*job, run)))
+    .collect::<Vec<_>>();
+
+  let next = AtomicUsize::new(0);
+  let reports = Mutex::new(vec![None; queue.len()]);
+
+  thread::scope(|scope| {
+    for _ in 0..jobs.clamp(1, queue.len().max(1)) {
+      scope.spawn(|| loop {
+        let index = next.fetch_add(1, Ordering::Relaxed);
+        let Some(&(
+          Job {
+            trace,
+            algorithm,
+            frames,
+          },
+          run,
+        )) = queue.get(index)
+        else {
+          break;
+        };
+
+        let (name, trace) = &traces[trace];
+        let options = PALOptions {
+          seed: options.seed.wrapping_add(run),
+          ..options.clone()
+        };
+        let mut simulator = Simulator::with_options(algorithm, frames, page_size, &options);
+        simulator.run(trace);
+
+        reports.lock().unwrap()[index] = Some(match simulator.pal.error() {
+          Some(error) => Err(error.to_string()),
+          None => Ok(simulator.report(name)),
+        });
+      });
+    }
+  });
+
+  // the runs of a simulation are next to each other in the queue, the first one reports
+  let mut reports = reports.into_inner().unwrap().into_iter().flatten();
+  simulations
+    .iter()
+    .map(|job| {
+      let runs = reports
+        .by_ref()
+        .take(runs(job))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(anyhow::Error::msg)?;
+      let mut report = runs[0].clone();
+      if seeds > 1 {
+        let misses = runs.iter().map(|run| run.misses).collect::<Vec<_>>();
+        report.seeds = Some(SeedsReport::new(options.seed, &misses));
+      }
+      Ok(report)
+    })
+    .collect()
+}
+
+pub fn entrypoint(
+  SweepOptions {
+    page_table_size,
+    frames,
+    algorithm,
+    traces,
+    jobs,
+    pal,
+    seeds,
+    output,
+  }: &SweepOptions,
+) -> anyhow::Result<String> {
+  let traces = traces
+    .iter()
+    .map(|path| Ok((path.clone(), Trace::from_file(path)?)))
+    .collect::<anyhow::Result<Vec<_>>>()?;
+  let frames = frames
+    .iter()
+    .flat_map(|counts| counts.0.iter().copied())
+    .collect::<Vec<_>>();
+  let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+  pal.check(algorithm)?;
+
+  let reports = sweep(
+    &traces,
+    algorithm,
+    &frames,
+    *page_table_size,
+    pal,
+    *seeds,
+    jobs,
+  )?;
+
+  report::render_all(&reports, output)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use crate::{
+    cli::{sweep::FrameCounts, Output},
+    mmu::address::LogicalAddress,
+  };
+
+  use super::*;
+
+  fn trace(addresses: &[&str]) -> Trace {
+    let mut trace = Trace::new();
+    addresses
+      .iter()
+      .for_each(|address| trace.add(LogicalAddress::from_str(address).unwrap()));
+    trace
+  }
+
+  #[test]
+  fn frame_counts() {
+    assert_eq!(FrameCounts::from_str("64"), Ok(FrameCounts(vec![64])));
+    assert_eq!(
+      FrameCounts::from_str("1..16"),
+      Ok(FrameCounts(vec![1, 2, 4, 8, 16]))
+    );
+    assert_eq!(FrameCounts::from_str("4..20"), Ok(FrameCounts(vec![4, 8, 16])));
+    assert!(FrameCounts::from_str("3..16").is_err());
+    assert!(FrameCounts::from_str("0").is_err());
+    assert!(FrameCounts::from_str("0..4").is_err());
+    assert!(FrameCounts::from_str("lots").is_err());
+  }
+
+  #[test]
+  fn matches_single_runs() {
+    let traces = vec![
+      (
+        "a".to_string(),
+        trace(&["001123", "002123", "001123", "003123", "005123", "006123"]),
+      ),
+      (
+        "b".to_string(),
+        trace(&["001000", "002000", "003000", "001000", "004000", "002000"]),
+      ),
+    ];
+    let algorithms = [PALAlgorithm::LRU, PALAlgorithm::Fifo, PALAlgorithm::Optimal];
+    let frames = [1, 2, 4];
+
+    let reports =
+      sweep(&traces, &algorithms, &frames, 4096, &PALOptions::default(), 1, 4).unwrap();
+    assert_eq!(reports.len(), 18);
+
+    let mut reports = reports.iter();
+    for (name, trace) in traces.iter() {
+      for algorithm in algorithms {
+        for frames in frames {
+          let mut simulator = Simulator::new(algorithm, frames, 4096);
+          simulator.run(trace);
+          let expected = simulator.report(name);
+
+          let report = reports.next().unwrap();
+          assert_eq!(report.row(), expected.row());
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn seeds() {
+    let addresses = (0..60)
+      .map(|i| format!("{:03x}000", (i * 5) % 7))
+      .collect::<Vec<_>>();
+    let traces = vec![(
+      "a".to_string(),
+      trace(&addresses.iter().map(String::as_str).collect::<Vec<_>>()),
+    )];
+    let options = PALOptions {
+      seed: 5,
+      ..PALOptions::default()
+    };
+
+    let algorithms = [PALAlgorithm::LRU, PALAlgorithm::Random];
+    let reports = sweep(&traces, &algorithms, &[3], 4096, &options, 4, 2).unwrap();
+    assert_eq!(reports.len(), 2);
+
+    // the seed changes nothing for LRU, which runs once
+    let lru = reports[0].seeds.as_ref().unwrap();
+    assert_eq!((lru.runs, lru.misses_stddev), (1, 0.0));
+    assert_eq!(lru.misses_mean, reports[0].misses as f64);
+
+    // random runs with seeds 5 to 8, the first one giving the rest of the report
+    let misses = (5..9)
+      .map(|seed| {
+        let options = PALOptions {
+          seed,
+          ..options.clone()
+        };
+        let mut simulator = Simulator::with_options(PALAlgorithm::Random, 3, 4096, &options);
+        simulator.run(&traces[0].1);
+        simulator.statistics.misses
+      })
+      .collect::<Vec<_>>();
+    let random = reports[1].seeds.as_ref().unwrap();
+    assert_eq!((random.seed, random.runs), (5, 4));
+    assert_eq!(random.misses_mean, misses.iter().sum::<usize>() as f64 / 4.0);
+    assert_eq!(reports[1].misses, misses[0]);
+
+    assert_eq!(SeedsReport::new(0, &[1, 3]).misses_stddev, 2f64.sqrt());
+  }
+
+  #[test]
+  fn mixed_sections() {
+    let traces = vec![("a".to_string(), trace(&["001000", "002000", "003000", "001000"]))];
+    let algorithms = [PALAlgorithm::LRU, PALAlgorithm::WorkingSet];
+    let reports =
+      sweep(&traces, &algorithms, &[2], 4096, &PALOptions::default(), 1, 2).unwrap();
+
+    // only the working set run has its section, the LRU row leaves those columns empty
+    let csv = report::render_all(&reports, &Output::Csv).unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert!(lines[0].ends_with(",tau,working_set_mean,working_set_max,mean_resident,released"));
+    assert!(lines[1].ends_with(",,,,,"));
+    assert!(lines
+      .iter()
+      .all(|line| line.split(',').count() == lines[0].split(',').count()));
+  }
+
+  #[test]
+  fn unmappable_pages() {
+    let traces = vec![
+      ("a".to_string(), trace(&["001000"])),
+      ("b".to_string(), trace(&["001000", "100000000"])),
+    ];
+    let error = sweep(&traces, &[PALAlgorithm::LRU], &[2], 4096, &PALOptions::default(), 1, 2)
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "b: address 100000000: Page 0x100000 is beyond the 1048576 pages of the table"
+    );
+  }
+}
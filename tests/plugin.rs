@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use pal_rs::pal::{plugin::Plugin, PALAlgorithm, PAL};
+
+/// The example plugin, which cargo builds next to this test as one of its dev-dependencies
+fn example_plugin() -> PathBuf {
+  let deps = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+  deps.join(libloading::library_filename("pal_plugin_lru"))
+}
+
+#[test]
+fn loads_the_example_plugin() {
+  let library = example_plugin();
+  let plugin = Plugin::load(library.to_str().unwrap()).unwrap();
+  assert_eq!(plugin.name, "lru");
+  // loaded once, whatever the number of tables running it
+  assert!(std::ptr::eq(plugin, Plugin::load(&plugin.path).unwrap()));
+
+  let references = (0..300).map(|i| (i * i + 3 * i) % 9).collect::<Vec<usize>>();
+  let victims = |mut pal: PAL| {
+    references
+      .iter()
+      .filter_map(|frame| pal.insert(*frame))
+      .collect::<Vec<_>>()
+  };
+  let mut pal = PAL::new(PALAlgorithm::Plugin(plugin), 4);
+  // clones run on their own state
+  let clone = pal.clone();
+  pal.insert(8);
+  assert_eq!(clone.resident(), 0);
+  assert_eq!(pal.resident(), 1);
+
+  assert_eq!(
+    victims(PAL::new(PALAlgorithm::Plugin(plugin), 4)),
+    victims(PAL::new(PALAlgorithm::LRU, 4))
+  );
+  assert_eq!(
+    PALAlgorithm::Plugin(plugin).to_string(),
+    format!("plugin:{}", plugin.path)
+  );
+}